@@ -0,0 +1,159 @@
+//! What screen readers are told about the timer: changes of state and a
+//! few milestones as time runs down, never every second
+
+use crate::timer_service::TimerSnapshot;
+
+/// `seconds`, ignoring sign, as words, e.g. "4 minutes 30 seconds"
+pub fn spoken_time(seconds: i32) -> String {
+    let seconds = seconds.unsigned_abs();
+    let unit = |n: u32, name: &str| {
+        if n == 1 {
+            format!("1 {name}")
+        } else {
+            format!("{n} {name}s")
+        }
+    };
+    match (seconds / 60, seconds % 60) {
+        (0, s) => unit(s, "second"),
+        (m, 0) => unit(m, "minute"),
+        (m, s) => format!("{} {}", unit(m, "minute"), unit(s, "second")),
+    }
+}
+
+/// Worth calling out when counted down to: every five minutes and the last
+/// minute, half minute and ten seconds
+fn is_milestone(remaining: i32) -> bool {
+    remaining > 0 && (remaining % 300 == 0 || matches!(remaining, 60 | 30 | 10))
+}
+
+/// What to announce on the timer going from `previous` to `current`, if
+/// anything
+pub fn announcement(previous: &TimerSnapshot, current: &TimerSnapshot) -> Option<String> {
+    let left = spoken_time(current.remaining);
+    if current.editing != previous.editing {
+        return Some(if current.editing {
+            "Entering a new time".into()
+        } else if current.remaining != previous.remaining {
+            format!("Set to {left}")
+        } else {
+            format!("Time entry cancelled, {left} left")
+        });
+    }
+    if current.blinking && !previous.blinking {
+        return Some("Time is up".into());
+    }
+    if current.paused != previous.paused {
+        return Some(if current.paused {
+            format!("Paused with {left} left")
+        } else {
+            format!("Resumed with {left} left")
+        });
+    }
+    if current.remaining > previous.remaining {
+        return Some(format!("Reset to {left}"));
+    }
+    if current.remaining != previous.remaining - 1 {
+        return None;
+    }
+    if is_milestone(current.remaining) {
+        Some(format!("{left} left"))
+    } else if current.remaining < 0 && current.remaining % 300 == 0 {
+        Some(format!("{left} over"))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(remaining: i32) -> TimerSnapshot {
+        TimerSnapshot {
+            remaining,
+            ..TimerSnapshot::new(1500)
+        }
+    }
+
+    #[test]
+    fn test_spoken_time() {
+        assert_eq!(spoken_time(1500), "25 minutes");
+        assert_eq!(spoken_time(270), "4 minutes 30 seconds");
+        assert_eq!(spoken_time(61), "1 minute 1 second");
+        assert_eq!(spoken_time(0), "0 seconds");
+        assert_eq!(spoken_time(-600), "10 minutes");
+    }
+
+    #[test]
+    fn test_milestones_only() {
+        assert_eq!(announcement(&at(302), &at(301)), None);
+        assert_eq!(
+            announcement(&at(301), &at(300)),
+            Some("5 minutes left".into())
+        );
+        assert_eq!(announcement(&at(61), &at(60)), Some("1 minute left".into()));
+        assert_eq!(
+            announcement(&at(11), &at(10)),
+            Some("10 seconds left".into())
+        );
+        assert_eq!(announcement(&at(9), &at(8)), None);
+        assert_eq!(
+            announcement(&at(-299), &at(-300)),
+            Some("5 minutes over".into())
+        );
+    }
+
+    #[test]
+    fn test_state_changes() {
+        let running = at(100);
+        let paused = TimerSnapshot {
+            paused: true,
+            ..running.clone()
+        };
+        assert_eq!(
+            announcement(&running, &paused),
+            Some("Paused with 1 minute 40 seconds left".into())
+        );
+        assert_eq!(
+            announcement(&paused, &running),
+            Some("Resumed with 1 minute 40 seconds left".into())
+        );
+
+        let up = TimerSnapshot {
+            blinking: true,
+            ..at(-1)
+        };
+        assert_eq!(announcement(&at(0), &up), Some("Time is up".into()));
+        // The blink itself is not news
+        let blinked = TimerSnapshot {
+            blink_visible: false,
+            ..up.clone()
+        };
+        assert_eq!(announcement(&up, &blinked), None);
+        assert_eq!(
+            announcement(&blinked, &at(1500)),
+            Some("Reset to 25 minutes".into())
+        );
+    }
+
+    #[test]
+    fn test_time_entry() {
+        let editing = TimerSnapshot {
+            editing: true,
+            paused: true,
+            ..at(100)
+        };
+        assert_eq!(
+            announcement(&at(100), &editing),
+            Some("Entering a new time".into())
+        );
+        assert_eq!(
+            announcement(&editing, &at(600)),
+            Some("Set to 10 minutes".into())
+        );
+        assert_eq!(
+            announcement(&editing, &at(100)),
+            Some("Time entry cancelled, 1 minute 40 seconds left".into())
+        );
+    }
+}
@@ -0,0 +1,329 @@
+//! Key combinations written as text, e.g. `CmdOrCtrl+Shift+L`. The key is
+//! a `KeyboardEvent.key` value such as `p`, or a `KeyboardEvent.code` such
+//! as `KeyP` for the key in that place whatever the layout.
+
+use std::fmt;
+
+/// A key with the modifiers that must be held with it, and no others
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Chord {
+    /// A `KeyboardEvent.key` or `.code` value, matched ignoring case
+    pub key: String,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub meta: bool,
+    /// Cmd on macOS, Ctrl elsewhere: either one satisfies it
+    pub cmd_or_ctrl: bool,
+}
+
+/// The modifiers held during a key press
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub meta: bool,
+}
+
+/// A key press, as the window saw it
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeyPress {
+    /// `KeyboardEvent.key`: what the layout made of it
+    pub key: String,
+    /// `KeyboardEvent.code`: where the key is
+    pub code: String,
+    pub held: Modifiers,
+}
+
+impl KeyPress {
+    /// The key as a Latin layout would name it. On a Cyrillic or Greek
+    /// layout, or with an input method composing, `key` is some other
+    /// letter or `Process`; the key in the same place stands in for it.
+    pub fn latin_key(&self) -> Option<&str> {
+        let foreign =
+            !self.key.is_ascii() || matches!(self.key.as_str(), "Process" | "Unidentified");
+        if foreign {
+            latin_key(&self.code)
+        } else {
+            Some(&self.key)
+        }
+    }
+
+    /// Whether it is only a modifier going down, which no chord is
+    pub fn is_modifier(&self) -> bool {
+        matches!(
+            self.key.as_str(),
+            "Shift" | "Control" | "Alt" | "AltGraph" | "Meta" | "OS" | "CapsLock"
+        )
+    }
+}
+
+/// The character a key makes on a US layout, by its `KeyboardEvent.code`
+fn latin_key(code: &str) -> Option<&str> {
+    if let Some(letter) = code.strip_prefix("Key").filter(|rest| rest.len() == 1) {
+        // Chords match ignoring case, so upper case does
+        return Some(letter);
+    }
+    if let Some(digit) = code.strip_prefix("Digit").filter(|rest| rest.len() == 1) {
+        return Some(digit);
+    }
+    Some(match code {
+        "Minus" => "-",
+        "Equal" => "=",
+        "BracketLeft" => "[",
+        "BracketRight" => "]",
+        "Backslash" => "\\",
+        "Semicolon" => ";",
+        "Quote" => "'",
+        "Backquote" => "`",
+        "Comma" => ",",
+        "Period" => ".",
+        "Slash" => "/",
+        _ => return None,
+    })
+}
+
+impl Chord {
+    /// `None` unless it is a key, alone or after modifiers joined by `+`.
+    /// The `+` key itself is written as such, e.g. `Ctrl++`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut chord = Chord::default();
+        let text = text.trim();
+        let (modifiers, key) = match text.strip_suffix('+') {
+            Some(rest) if rest.is_empty() || rest.ends_with('+') => (rest, "+"),
+            _ => match text.rsplit_once('+') {
+                Some((modifiers, key)) => (modifiers, key.trim()),
+                None => ("", text),
+            },
+        };
+        if key.is_empty() {
+            return None;
+        }
+        let parts = modifiers
+            .trim_end_matches('+')
+            .split('+')
+            .map(str::trim)
+            .filter(|_| !modifiers.is_empty());
+        for part in parts {
+            let held = match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => &mut chord.ctrl,
+                "alt" | "option" => &mut chord.alt,
+                "shift" => &mut chord.shift,
+                "cmd" | "meta" | "super" => &mut chord.meta,
+                "cmdorctrl" => &mut chord.cmd_or_ctrl,
+                _ => return None,
+            };
+            if *held {
+                return None;
+            }
+            *held = true;
+        }
+        chord.key = key.into();
+        Some(chord)
+    }
+
+    /// The chord `press` would be bound as, `None` for a modifier alone.
+    /// Letters are kept as their Latin letter and other keys by name.
+    pub fn from_press(press: &KeyPress) -> Option<Self> {
+        if press.is_modifier() {
+            return None;
+        }
+        let key = match press.latin_key() {
+            Some(" ") | None => press.code.clone(),
+            Some(key) if key.chars().count() == 1 => key.to_lowercase(),
+            Some(key) => key.into(),
+        };
+        let held = press.held;
+        Some(Chord {
+            // A symbol already says whether Shift made it
+            shift: held.shift && !is_symbol(&key),
+            alt: held.alt,
+            cmd_or_ctrl: held.ctrl != held.meta,
+            ctrl: held.ctrl && held.meta,
+            meta: held.ctrl && held.meta,
+            key,
+        })
+    }
+
+    pub fn matches(&self, press: &KeyPress) -> bool {
+        let held = press.held;
+        let (ctrl, meta) = if self.cmd_or_ctrl {
+            // Exactly one of them
+            (held.ctrl != held.meta, true)
+        } else {
+            (held.ctrl == self.ctrl, held.meta == self.meta)
+        };
+        // Which symbols need Shift depends on the layout
+        let shift = held.shift == self.shift || (!self.shift && is_symbol(&self.key));
+        let key = |key: &str| key.eq_ignore_ascii_case(&self.key);
+        ctrl && meta
+            && shift
+            && held.alt == self.alt
+            && (key(&press.key) || key(&press.code) || press.latin_key().is_some_and(key))
+    }
+
+    /// Whether some key press matches both, as `matches` sees them: a key
+    /// by its place is the Latin key there, and a symbol without Shift
+    /// takes it either way
+    pub fn overlaps(&self, other: &Chord) -> bool {
+        let held = |chord: &Chord| {
+            if chord.cmd_or_ctrl {
+                vec![(true, false), (false, true)]
+            } else {
+                vec![(chord.ctrl, chord.meta)]
+            }
+        };
+        let any_shift = |chord: &Chord| !chord.shift && is_symbol(&chord.key);
+        self.latin_key().eq_ignore_ascii_case(other.latin_key())
+            && self.alt == other.alt
+            && (self.shift == other.shift || any_shift(self) || any_shift(other))
+            && held(self).iter().any(|pair| held(other).contains(pair))
+    }
+
+    /// The key, or for a place such as `KeyP` the key a US layout has there
+    fn latin_key(&self) -> &str {
+        latin_key(&self.key).unwrap_or(&self.key)
+    }
+
+    /// Whether any modifier is held
+    pub fn has_modifiers(&self) -> bool {
+        self.ctrl || self.alt || self.shift || self.meta || self.cmd_or_ctrl
+    }
+}
+
+/// A single character that is not a letter or digit
+fn is_symbol(key: &str) -> bool {
+    let mut chars = key.chars();
+    matches!((chars.next(), chars.next()), (Some(c), None) if !c.is_alphanumeric())
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let held = [
+            (self.cmd_or_ctrl, "CmdOrCtrl"),
+            (self.ctrl, "Ctrl"),
+            (self.meta, "Cmd"),
+            (self.alt, "Alt"),
+            (self.shift, "Shift"),
+        ];
+        for (_, name) in held.iter().filter(|(on, _)| *on) {
+            write!(f, "{name}+")?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(key: &str, code: &str, held: Modifiers) -> KeyPress {
+        KeyPress {
+            key: key.into(),
+            code: code.into(),
+            held,
+        }
+    }
+
+    fn held(ctrl: bool, meta: bool, shift: bool) -> Modifiers {
+        Modifiers {
+            ctrl,
+            meta,
+            shift,
+            alt: false,
+        }
+    }
+
+    const NONE: Modifiers = Modifiers {
+        ctrl: false,
+        alt: false,
+        shift: false,
+        meta: false,
+    };
+
+    #[test]
+    fn test_parse_and_display() {
+        let chord = Chord::parse("CmdOrCtrl+Shift+L").unwrap();
+        assert!(chord.cmd_or_ctrl && chord.shift && !chord.alt);
+        assert_eq!(chord.key, "L");
+        assert_eq!(chord.to_string(), "CmdOrCtrl+Shift+L");
+        assert_eq!(Chord::parse("alt + x").unwrap().to_string(), "Alt+x");
+        assert_eq!(Chord::parse("p").unwrap().to_string(), "p");
+        assert_eq!(Chord::parse("Ctrl++").unwrap().to_string(), "Ctrl++");
+        assert_eq!(Chord::parse("+").unwrap().key, "+");
+
+        assert_eq!(Chord::parse(""), None);
+        assert_eq!(Chord::parse("Ctrl+"), None);
+        assert_eq!(Chord::parse("Hyper+L"), None);
+        assert_eq!(Chord::parse("Ctrl+Ctrl+L"), None);
+    }
+
+    #[test]
+    fn test_matches_exact_modifiers() {
+        let chord = Chord::parse("CmdOrCtrl+Shift+L").unwrap();
+        // Shift makes the key upper case
+        assert!(chord.matches(&press("L", "KeyL", held(true, false, true))));
+        assert!(chord.matches(&press("l", "KeyL", held(false, true, true))));
+        assert!(!chord.matches(&press("L", "KeyL", held(true, true, true))));
+        assert!(!chord.matches(&press("L", "KeyL", held(true, false, false))));
+        assert!(!chord.matches(&press("K", "KeyK", held(true, false, true))));
+
+        let plain = Chord::parse("p").unwrap();
+        assert!(plain.matches(&press("p", "KeyP", NONE)));
+        assert!(!plain.matches(&press("p", "KeyP", held(true, false, false))));
+        assert!(!plain.matches(&press("P", "KeyP", held(false, false, true))));
+
+        // Shift is part of a symbol on some layouts and not others
+        let question = Chord::parse("?").unwrap();
+        assert!(question.matches(&press("?", "Slash", held(false, false, true))));
+    }
+
+    #[test]
+    fn test_matches_on_other_layouts() {
+        let plain = Chord::parse("p").unwrap();
+        // Russian layout, and an input method composing
+        assert!(plain.matches(&press("з", "KeyP", NONE)));
+        assert!(plain.matches(&press("Process", "KeyP", NONE)));
+        assert!(!plain.matches(&press("з", "KeyL", NONE)));
+        let undo = Chord::parse("CmdOrCtrl+z").unwrap();
+        assert!(undo.matches(&press("я", "KeyZ", held(true, false, false))));
+
+        // By place: Dvorak's `l` is where QWERTY's `p` is
+        let physical = Chord::parse("KeyP").unwrap();
+        assert!(physical.matches(&press("l", "KeyP", NONE)));
+        assert!(!physical.matches(&press("p", "KeyR", NONE)));
+    }
+
+    #[test]
+    fn test_from_press() {
+        let chord =
+            |key, code, held| Chord::from_press(&press(key, code, held)).map(|c| c.to_string());
+        assert_eq!(chord("p", "KeyP", NONE).as_deref(), Some("p"));
+        assert_eq!(chord("з", "KeyP", NONE).as_deref(), Some("p"));
+        assert_eq!(
+            chord("Z", "KeyZ", held(true, false, true)).as_deref(),
+            Some("CmdOrCtrl+Shift+z")
+        );
+        assert_eq!(
+            chord("?", "Slash", held(false, false, true)).as_deref(),
+            Some("?")
+        );
+        assert_eq!(chord(" ", "Space", NONE).as_deref(), Some("Space"));
+        assert_eq!(chord("F5", "F5", NONE).as_deref(), Some("F5"));
+        assert_eq!(chord("Shift", "ShiftLeft", held(false, false, true)), None);
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let chord = |text| Chord::parse(text).unwrap();
+        assert!(chord("CmdOrCtrl+z").overlaps(&chord("Ctrl+Z")));
+        assert!(chord("p").overlaps(&chord("P")));
+        assert!(!chord("CmdOrCtrl+z").overlaps(&chord("Ctrl+Cmd+z")));
+        assert!(!chord("p").overlaps(&chord("Shift+p")));
+        assert!(chord("p").overlaps(&chord("KeyP")));
+        assert!(chord("CmdOrCtrl+-").overlaps(&chord("Ctrl+Minus")));
+        assert!(chord("?").overlaps(&chord("Shift+?")));
+        assert!(!chord("Slash").overlaps(&chord("Shift+Slash")));
+    }
+}
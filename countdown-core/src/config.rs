@@ -1,8 +1,11 @@
-/// Application configuration constants
+//! Application configuration constants, and the defaults for `Settings`
 
 /// 25:00 in seconds for the initial countdown
 pub const INITIAL_SECONDS: i32 = 25 * 60;
 
+/// 59:59 in seconds, the longest duration that can be entered
+pub const MAXIMUM_SECONDS: i32 = 59 * 60 + 59;
+
 /// -59:59 in seconds for the stop condition
 pub const MINIMUM_SECONDS: i32 = -3599;
 
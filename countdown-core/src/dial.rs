@@ -0,0 +1,80 @@
+//! Geometry for the analog dial: a disc on a 60-minute face, like a kitchen
+//! "time timer", drawn as SVG paths in a 100-unit box centred on the origin
+
+use crate::config::MAXIMUM_SECONDS;
+
+/// Radius of the disc
+pub const DIAL_RADIUS: f64 = 45.0;
+
+/// Share of the face the disc covers. Remaining time fills anticlockwise
+/// from twelve; overrun is measured the same way but drawn clockwise.
+pub fn dial_fraction(remaining: i32) -> f64 {
+    (f64::from(remaining.abs()) / f64::from(MAXIMUM_SECONDS + 1)).min(1.0)
+}
+
+/// SVG path of a sector covering `fraction` of the face from twelve o'clock,
+/// anticlockwise unless `clockwise`. Empty for nothing, a full disc for 1.
+pub fn sector_path(fraction: f64, clockwise: bool) -> String {
+    let r = DIAL_RADIUS;
+    if fraction <= 0.0 {
+        return String::new();
+    }
+    if fraction >= 1.0 {
+        // Two half-circle arcs: a single arc cannot end where it starts
+        return format!(
+            "M 0 {} A {r} {r} 0 1 1 0 {r} A {r} {r} 0 1 1 0 {} Z",
+            -r, -r
+        );
+    }
+    let angle = fraction * std::f64::consts::TAU;
+    let x = if clockwise {
+        r * angle.sin()
+    } else {
+        -r * angle.sin()
+    };
+    let y = -r * angle.cos();
+    let large_arc = u8::from(fraction > 0.5);
+    let sweep = u8::from(clockwise);
+    format!(
+        "M 0 0 L 0 {} A {r} {r} 0 {large_arc} {sweep} {x:.2} {y:.2} Z",
+        -r
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dial_fraction() {
+        assert_eq!(dial_fraction(0), 0.0);
+        assert_eq!(dial_fraction(1800), 0.5);
+        assert_eq!(dial_fraction(-900), 0.25);
+        assert_eq!(dial_fraction(MAXIMUM_SECONDS + 1), 1.0);
+    }
+
+    #[test]
+    fn test_sector_path_quarter() {
+        // A quarter anticlockwise from twelve ends at nine o'clock
+        assert_eq!(
+            sector_path(0.25, false),
+            "M 0 0 L 0 -45 A 45 45 0 0 0 -45.00 -0.00 Z"
+        );
+        // and clockwise at three
+        assert_eq!(
+            sector_path(0.25, true),
+            "M 0 0 L 0 -45 A 45 45 0 0 1 45.00 -0.00 Z"
+        );
+    }
+
+    #[test]
+    fn test_sector_path_large_arc() {
+        assert!(sector_path(0.75, false).contains(" 0 1 0 "));
+    }
+
+    #[test]
+    fn test_sector_path_extremes() {
+        assert_eq!(sector_path(0.0, false), "");
+        assert!(sector_path(1.0, false).starts_with("M 0 -45 A 45 45 0 1 1 0 45"));
+    }
+}
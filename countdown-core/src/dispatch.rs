@@ -0,0 +1,260 @@
+//! One way in for every timer action. Keys, clicks, the tray, the windows'
+//! IPC, the control API and the command line all name a `TimerCommand`,
+//! which `dispatch` checks and applies, and all get the same answer back:
+//! whether anything changed, and the state after.
+
+use crate::config::MAXIMUM_SECONDS;
+use crate::event_logic::parse_time_input;
+use crate::timer_service::{TimerCommand, TimerEngine, TimerSnapshot};
+use std::fmt;
+
+/// A command as named from outside the windows
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    /// What its argument is, if it takes one
+    pub argument: Option<&'static str>,
+    /// Whether the argument may be left out
+    pub optional: bool,
+    pub summary: &'static str,
+}
+
+const fn spec(name: &'static str, summary: &'static str) -> CommandSpec {
+    CommandSpec {
+        name,
+        argument: None,
+        optional: false,
+        summary,
+    }
+}
+
+/// Every command that can be named; attaching is the windows' own business
+pub const COMMANDS: [CommandSpec; 11] = [
+    spec("start", "Restart from the set duration"),
+    spec("pause", "Pause"),
+    spec("resume", "Resume"),
+    spec("toggle_pause", "Pause or resume"),
+    spec("reset", "Reset, as a click on the time does"),
+    CommandSpec {
+        argument: Some("mm:ss"),
+        ..spec("set_duration", "Set a new duration and restart")
+    },
+    CommandSpec {
+        argument: Some("mm:ss"),
+        optional: true,
+        ..spec("snooze", "Once time is up, count down a little more")
+    },
+    spec("enter_edit", "Open time entry"),
+    spec("cancel_edit", "Leave time entry"),
+    spec("undo", "Take back the last reset or new duration"),
+    spec("redo", "Put it back"),
+];
+
+/// Why a command was refused before it reached the timer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CommandError {
+    Unknown(String),
+    /// The argument is missing, or given to a command that takes none
+    Argument(&'static str),
+    /// Not `mm:ss` or a number of seconds
+    BadDuration(String),
+    /// Seconds the timer cannot count down from
+    OutOfRange {
+        seconds: i32,
+        minimum: i32,
+    },
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Unknown(name) => write!(f, "no command named {name:?}"),
+            CommandError::Argument(name) => match find(name).and_then(|spec| spec.argument) {
+                Some(argument) => write!(f, "`{name}` takes an argument, {argument}"),
+                None => write!(f, "`{name}` takes no argument"),
+            },
+            CommandError::BadDuration(text) => {
+                write!(f, "expected mm:ss or a number of seconds, not {text:?}")
+            }
+            CommandError::OutOfRange { seconds, minimum } => write!(
+                f,
+                "seconds must be between {minimum} and {MAXIMUM_SECONDS}, not {seconds}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+/// Whether a command changed anything
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Outcome {
+    Changed,
+    /// A guard stopped it, such as resetting while paused, or there was
+    /// nothing to do
+    Unchanged,
+}
+
+/// What a dispatched command did
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dispatched {
+    pub outcome: Outcome,
+    pub state: TimerSnapshot,
+}
+
+fn find(name: &str) -> Option<&'static CommandSpec> {
+    COMMANDS.iter().find(|spec| spec.name == name)
+}
+
+/// The command `name`, with `argument` if it takes one, as the tray and
+/// command line name them
+pub fn parse(name: &str, argument: Option<&str>) -> Result<TimerCommand, CommandError> {
+    let spec = find(name).ok_or_else(|| CommandError::Unknown(name.into()))?;
+    let seconds = match (spec.argument, argument) {
+        (None, None) => None,
+        (Some(_), Some(text)) => Some(parse_seconds(text)?),
+        (Some(_), None) if spec.optional => None,
+        _ => return Err(CommandError::Argument(spec.name)),
+    };
+    Ok(match spec.name {
+        "start" => TimerCommand::Start,
+        "pause" => TimerCommand::Pause,
+        "resume" => TimerCommand::Resume,
+        "toggle_pause" => TimerCommand::TogglePause,
+        "reset" => TimerCommand::Reset,
+        "set_duration" => TimerCommand::SetDuration {
+            seconds: seconds.unwrap_or_default(),
+        },
+        "snooze" => TimerCommand::Snooze { seconds },
+        "enter_edit" => TimerCommand::EnterEdit,
+        "cancel_edit" => TimerCommand::CancelEdit,
+        "undo" => TimerCommand::Undo,
+        "redo" => TimerCommand::Redo,
+        // A name in `COMMANDS` this has not learnt yet
+        _ => return Err(CommandError::Unknown(name.into())),
+    })
+}
+
+/// `mm:ss`, or whole seconds
+fn parse_seconds(text: &str) -> Result<i32, CommandError> {
+    parse_time_input(text)
+        .or_else(|| text.trim().parse().ok())
+        .ok_or_else(|| CommandError::BadDuration(text.into()))
+}
+
+/// Refuse durations the timer cannot count down from
+pub fn validate(command: &TimerCommand) -> Result<(), CommandError> {
+    let (seconds, minimum) = match *command {
+        TimerCommand::Attach {
+            saved_seconds: Some(seconds),
+        }
+        | TimerCommand::SetDuration { seconds } => (seconds, 0),
+        // A snooze of nothing would be time up again at once
+        TimerCommand::Snooze {
+            seconds: Some(seconds),
+        } => (seconds, 1),
+        _ => return Ok(()),
+    };
+    if (minimum..=MAXIMUM_SECONDS).contains(&seconds) {
+        Ok(())
+    } else {
+        Err(CommandError::OutOfRange { seconds, minimum })
+    }
+}
+
+/// Check `command` and apply it to `engine`
+pub fn dispatch(engine: &TimerEngine, command: TimerCommand) -> Result<Dispatched, CommandError> {
+    validate(&command)?;
+    let before = engine.snapshot();
+    engine.apply(command);
+    let state = engine.snapshot();
+    let outcome = if state == before {
+        Outcome::Unchanged
+    } else {
+        Outcome::Changed
+    };
+    Ok(Dispatched { outcome, state })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer_service::tests::attached;
+
+    #[test]
+    fn test_parse_every_command() {
+        for spec in COMMANDS {
+            let argument = spec.argument.map(|_| "05:00");
+            assert!(parse(spec.name, argument).is_ok(), "{}", spec.name);
+        }
+        assert_eq!(
+            parse("set_duration", Some("300")),
+            Ok(TimerCommand::SetDuration { seconds: 300 })
+        );
+        assert_eq!(
+            parse("snooze", None),
+            Ok(TimerCommand::Snooze { seconds: None })
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("explode", None),
+            Err(CommandError::Unknown("explode".into()))
+        );
+        assert_eq!(
+            parse("set_duration", None),
+            Err(CommandError::Argument("set_duration"))
+        );
+        assert_eq!(
+            parse("pause", Some("1")),
+            Err(CommandError::Argument("pause"))
+        );
+        assert_eq!(
+            parse("set_duration", Some("soon")),
+            Err(CommandError::BadDuration("soon".into()))
+        );
+        assert_eq!(
+            parse("set_duration", None).unwrap_err().to_string(),
+            "`set_duration` takes an argument, mm:ss"
+        );
+    }
+
+    #[test]
+    fn test_dispatch_reports_outcome() {
+        let engine = attached(1500);
+        let paused = dispatch(&engine, TimerCommand::Pause).unwrap();
+        assert_eq!(paused.outcome, Outcome::Changed);
+        assert!(paused.state.paused);
+        // Paused already, and reset is ignored while paused
+        for command in [TimerCommand::Pause, TimerCommand::Reset] {
+            let ignored = dispatch(&engine, command).unwrap();
+            assert_eq!(ignored.outcome, Outcome::Unchanged);
+            assert_eq!(ignored.state, paused.state);
+        }
+    }
+
+    #[test]
+    fn test_dispatch_refuses_bad_durations() {
+        let engine = attached(1500);
+        let before = engine.snapshot();
+        for command in [
+            TimerCommand::SetDuration { seconds: 3600 },
+            TimerCommand::SetDuration { seconds: -1 },
+            TimerCommand::Snooze { seconds: Some(0) },
+        ] {
+            assert!(matches!(
+                dispatch(&engine, command),
+                Err(CommandError::OutOfRange { .. })
+            ));
+        }
+        assert_eq!(engine.snapshot(), before);
+    }
+}
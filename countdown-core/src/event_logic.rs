@@ -29,7 +29,7 @@ pub fn handle_click(
 /// Format time input with automatic colon insertion
 pub fn format_time_input(value: &str) -> String {
     // Remove any non-digit characters
-    let digits: String = value.chars().filter(|c| c.is_digit(10)).collect();
+    let digits: String = value.chars().filter(|c| c.is_ascii_digit()).collect();
 
     match digits.len() {
         0 => String::new(),
@@ -69,18 +69,18 @@ mod tests {
     fn click_when_not_blinking() {
         let result = handle_click(42, false, None);
         assert_eq!(result.reset_remaining, INITIAL_SECONDS);
-        assert_eq!(result.should_cancel_blink, false);
-        assert_eq!(result.is_blinking, false);
-        assert_eq!(result.is_visible, true);
+        assert!(!result.should_cancel_blink);
+        assert!(!result.is_blinking);
+        assert!(result.is_visible);
     }
 
     #[test]
     fn click_when_blinking() {
         let result = handle_click(0, true, None);
         assert_eq!(result.reset_remaining, INITIAL_SECONDS);
-        assert_eq!(result.should_cancel_blink, true);
-        assert_eq!(result.is_blinking, false);
-        assert_eq!(result.is_visible, true);
+        assert!(result.should_cancel_blink);
+        assert!(!result.is_blinking);
+        assert!(result.is_visible);
     }
 
     #[test]
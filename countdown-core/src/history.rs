@@ -0,0 +1,130 @@
+//! Undo and redo for the timer: the state before each reset, restart or
+//! newly set duration is kept, a bounded number of them, so a stray click
+//! or Enter can be taken back
+
+use crate::timer_service::TimerSnapshot;
+use std::collections::VecDeque;
+
+/// States kept to undo to; the oldest are forgotten first
+pub const HISTORY_LIMIT: usize = 20;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct History {
+    undo: VecDeque<TimerSnapshot>,
+    redo: Vec<TimerSnapshot>,
+}
+
+impl History {
+    /// Remember `before` as the state a change left. What was undone can no
+    /// longer be redone.
+    pub fn record(&mut self, before: TimerSnapshot) {
+        if self.undo.len() == HISTORY_LIMIT {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(before);
+        self.redo.clear();
+    }
+
+    /// The state to go back to from `current`, which `redo` returns to
+    pub fn undo(&mut self, current: TimerSnapshot) -> Option<TimerSnapshot> {
+        let previous = self.undo.pop_back()?;
+        self.redo.push(current);
+        Some(previous)
+    }
+
+    /// The state undone from, going back to `current` on the next `undo`
+    pub fn redo(&mut self, current: TimerSnapshot) -> Option<TimerSnapshot> {
+        let next = self.redo.pop()?;
+        self.undo.push_back(current);
+        Some(next)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+/// Whether going from `previous` to `current` is worth offering to undo: a
+/// jump back up or a new duration, rather than a tick or a pause
+pub fn offers_undo(previous: &TimerSnapshot, current: &TimerSnapshot) -> bool {
+    current.can_undo
+        && !current.editing
+        && (current.remaining > previous.remaining || current.reset_time != previous.reset_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(remaining: i32) -> TimerSnapshot {
+        TimerSnapshot {
+            remaining,
+            ..TimerSnapshot::new(1500)
+        }
+    }
+
+    #[test]
+    fn test_undo_then_redo() {
+        let mut history = History::default();
+        assert!(!history.can_undo());
+        assert_eq!(history.undo(at(1500)), None);
+
+        history.record(at(42));
+        assert!(history.can_undo());
+        assert_eq!(history.undo(at(1500)), Some(at(42)));
+        assert!(!history.can_undo());
+        assert!(history.can_redo());
+        assert_eq!(history.redo(at(42)), Some(at(1500)));
+        assert_eq!(history.undo(at(1500)), Some(at(42)));
+    }
+
+    #[test]
+    fn test_new_change_forgets_redo() {
+        let mut history = History::default();
+        history.record(at(42));
+        history.undo(at(1500));
+        history.record(at(1400));
+        assert!(!history.can_redo());
+        assert_eq!(history.redo(at(600)), None);
+        assert_eq!(history.undo(at(600)), Some(at(1400)));
+    }
+
+    #[test]
+    fn test_bounded() {
+        let mut history = History::default();
+        for remaining in 0..HISTORY_LIMIT as i32 + 5 {
+            history.record(at(remaining));
+        }
+        let mut undone = Vec::new();
+        while let Some(state) = history.undo(at(0)) {
+            undone.push(state.remaining);
+        }
+        assert_eq!(undone.len(), HISTORY_LIMIT);
+        // The oldest went first
+        assert_eq!(undone.last(), Some(&5));
+    }
+
+    #[test]
+    fn test_offers_undo_on_jumps_only() {
+        let undoable = |remaining| TimerSnapshot {
+            can_undo: true,
+            ..at(remaining)
+        };
+        // A reset
+        assert!(offers_undo(&undoable(-30), &undoable(1500)));
+        // A tick
+        assert!(!offers_undo(&undoable(100), &undoable(99)));
+        // A shorter duration entered
+        let set = TimerSnapshot {
+            reset_time: 300,
+            ..undoable(300)
+        };
+        assert!(offers_undo(&undoable(1000), &set));
+        // Nothing to undo
+        assert!(!offers_undo(&at(-30), &at(1500)));
+    }
+}
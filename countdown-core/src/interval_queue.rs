@@ -0,0 +1,157 @@
+//! The intervals behind a `TimerProvider` that is woken from outside, on
+//! whatever clock it keeps: `Instant` for the backend's timer thread, a
+//! virtual millisecond count for `ManualClockProvider`.
+
+use crate::timer_provider::TimerHandle;
+use std::cell::{Cell, RefCell};
+use std::ops::Add;
+use std::rc::Rc;
+
+type Callback = Rc<RefCell<Box<dyn FnMut()>>>;
+
+/// Intervals on a clock of `T`, with periods of `P`. Each is due at whole
+/// multiples of its period from when it was set, so late runs never
+/// accumulate into drift. One that fell behind (a suspended laptop, say)
+/// runs once and skips the periods it missed rather than replaying them
+/// all in a burst. Due callbacks run earliest first, and on a tie in the
+/// order their intervals were set.
+pub struct IntervalQueue<T, P> {
+    intervals: RefCell<Vec<Interval<T, P>>>,
+}
+
+struct Interval<T, P> {
+    period: P,
+    due: T,
+    callback: Callback,
+    cancelled: Rc<Cell<bool>>,
+}
+
+impl<T, P> Default for IntervalQueue<T, P> {
+    fn default() -> Self {
+        Self {
+            intervals: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl<T, P> IntervalQueue<T, P>
+where
+    T: Copy + Ord + Add<P, Output = T>,
+    P: Copy,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `callback` every `period` from `now` until the handle is
+    /// cancelled. `period` must be more than nothing.
+    pub fn set_interval(
+        &self,
+        now: T,
+        period: P,
+        callback: Box<dyn FnMut()>,
+    ) -> Box<dyn TimerHandle> {
+        let cancelled = Rc::new(Cell::new(false));
+        self.intervals.borrow_mut().push(Interval {
+            period,
+            due: now + period,
+            callback: Rc::new(RefCell::new(callback)),
+            cancelled: cancelled.clone(),
+        });
+        Box::new(QueuedHandle { cancelled })
+    }
+
+    /// When the earliest live interval is next due, or `None` when none is
+    /// set
+    pub fn next_due(&self) -> Option<T> {
+        self.intervals
+            .borrow()
+            .iter()
+            .filter(|interval| !interval.cancelled.get())
+            .map(|interval| interval.due)
+            .min()
+    }
+
+    /// Run every callback due at or before `now`, each once however many
+    /// periods it missed, telling `reached` when each was due just before it
+    /// runs. Callbacks may set or cancel intervals, including their own.
+    pub fn run_due(&self, now: T, mut reached: impl FnMut(T)) {
+        loop {
+            let next = {
+                let mut intervals = self.intervals.borrow_mut();
+                intervals.retain(|interval| !interval.cancelled.get());
+                intervals
+                    .iter_mut()
+                    .filter(|interval| interval.due <= now)
+                    .min_by_key(|interval| interval.due)
+                    .map(|interval| {
+                        let due = interval.due;
+                        while interval.due <= now {
+                            interval.due = interval.due + interval.period;
+                        }
+                        (due, interval.callback.clone())
+                    })
+            };
+            match next {
+                Some((due, callback)) => {
+                    reached(due);
+                    (callback.borrow_mut())();
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+/// Cancelled intervals are dropped the next time the queue runs
+struct QueuedHandle {
+    cancelled: Rc<Cell<bool>>,
+}
+
+impl TimerHandle for QueuedHandle {
+    fn cancel(&mut self) {
+        self.cancelled.set(true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runs_due_on_any_clock() {
+        let queue = IntervalQueue::<u32, u32>::new();
+        let fired = Rc::new(RefCell::new(Vec::new()));
+        let (slow, fast) = (fired.clone(), fired.clone());
+        let _slow = queue.set_interval(0, 4, Box::new(move || slow.borrow_mut().push("slow")));
+        let mut fast_handle =
+            queue.set_interval(1, 3, Box::new(move || fast.borrow_mut().push("fast")));
+        assert_eq!(queue.next_due(), Some(4));
+
+        let mut reached = Vec::new();
+        queue.run_due(4, |due| reached.push(due));
+        queue.run_due(7, |due| reached.push(due));
+        queue.run_due(8, |due| reached.push(due));
+        assert_eq!(&*fired.borrow(), &["slow", "fast", "fast", "slow"]);
+        assert_eq!(reached, [4, 4, 7, 8]);
+
+        fast_handle.cancel();
+        assert_eq!(queue.next_due(), Some(12));
+    }
+
+    #[test]
+    fn test_missed_periods_run_once() {
+        let queue = IntervalQueue::<u32, u32>::new();
+        let fired = Rc::new(Cell::new(0));
+        let counter = fired.clone();
+        let _handle = queue.set_interval(0, 10, Box::new(move || counter.set(counter.get() + 1)));
+
+        // A thousand periods late, as after a sleep
+        let mut reached = Vec::new();
+        queue.run_due(10_005, |due| reached.push(due));
+        assert_eq!(fired.get(), 1);
+        assert_eq!(reached, [10]);
+        // Back on its grid, the next period after the wake-up
+        assert_eq!(queue.next_due(), Some(10_010));
+    }
+}
@@ -0,0 +1,247 @@
+//! Which key does what in the timer window: the chords in the settings,
+//! each bound to a named action. Chords match by the key's character, by
+//! its place, or by the Latin letter in that place, so the bindings work
+//! on any layout and while an input method is on; see `Chord`.
+
+use crate::chord::{Chord, KeyPress};
+use crate::settings::Settings;
+
+/// What a key can do
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    TogglePause,
+    EnterEdit,
+    CycleDisplay,
+    Grow,
+    Shrink,
+    Reset,
+    Snooze,
+    Undo,
+    Redo,
+    OpenSettings,
+    /// Handled ahead of the rest, since it also works while locked
+    Lock,
+}
+
+impl Action {
+    pub const ALL: [Action; 11] = [
+        Action::TogglePause,
+        Action::EnterEdit,
+        Action::CycleDisplay,
+        Action::Grow,
+        Action::Shrink,
+        Action::Reset,
+        Action::Snooze,
+        Action::Undo,
+        Action::Redo,
+        Action::OpenSettings,
+        Action::Lock,
+    ];
+
+    /// Where in the settings its chord is
+    pub fn setting(self) -> &'static str {
+        match self {
+            Action::TogglePause => "shortcuts.toggle_pause",
+            Action::EnterEdit => "shortcuts.enter_edit",
+            Action::CycleDisplay => "shortcuts.cycle_display",
+            Action::Grow => "shortcuts.grow",
+            Action::Shrink => "shortcuts.shrink",
+            Action::Reset => "shortcuts.reset",
+            Action::Snooze => "shortcuts.snooze",
+            Action::Undo => "shortcuts.undo",
+            Action::Redo => "shortcuts.redo",
+            Action::OpenSettings => "shortcuts.open_settings",
+            Action::Lock => "lock.chord",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::TogglePause => "Pause / resume",
+            Action::EnterEdit => "Enter time",
+            Action::CycleDisplay => "Next display",
+            Action::Grow => "Larger window",
+            Action::Shrink => "Smaller window",
+            Action::Reset => "Reset",
+            Action::Snooze => "Snooze",
+            Action::Undo => "Undo",
+            Action::Redo => "Redo",
+            Action::OpenSettings => "Open settings",
+            Action::Lock => "Lock",
+        }
+    }
+
+    /// Its chord as written in `settings`
+    fn chord_text(self, settings: &Settings) -> &str {
+        let keys = &settings.shortcuts;
+        match self {
+            Action::TogglePause => &keys.toggle_pause,
+            Action::EnterEdit => &keys.enter_edit,
+            Action::CycleDisplay => &keys.cycle_display,
+            Action::Grow => &keys.grow,
+            Action::Shrink => &keys.shrink,
+            Action::Reset => &keys.reset,
+            Action::Snooze => &keys.snooze,
+            Action::Undo => &keys.undo,
+            Action::Redo => &keys.redo,
+            Action::OpenSettings => &keys.open_settings,
+            Action::Lock => &settings.lock.chord,
+        }
+    }
+}
+
+/// An action and the chord for it
+#[derive(Clone, Debug, PartialEq)]
+pub struct Binding {
+    pub action: Action,
+    /// As written in the settings
+    pub text: String,
+    /// `None` if `text` is not a chord
+    pub chord: Option<Chord>,
+}
+
+/// Every action's binding, in `Action::ALL` order
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keymap {
+    bindings: Vec<Binding>,
+}
+
+impl Keymap {
+    pub fn new(settings: &Settings) -> Self {
+        let bindings = Action::ALL
+            .into_iter()
+            .map(|action| {
+                let text = action.chord_text(settings).to_string();
+                Binding {
+                    action,
+                    chord: Chord::parse(&text),
+                    text,
+                }
+            })
+            .collect();
+        Self { bindings }
+    }
+
+    pub fn bindings(&self) -> &[Binding] {
+        &self.bindings
+    }
+
+    /// The action `press` is bound to; where bindings clash, the first
+    pub fn action(&self, press: &KeyPress) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.chord.as_ref().is_some_and(|c| c.matches(press)))
+            .map(|binding| binding.action)
+    }
+
+    /// Pairs of bindings some key press would match both of
+    pub fn conflicts(&self) -> Vec<(&Binding, &Binding)> {
+        let mut conflicts = Vec::new();
+        for (i, binding) in self.bindings.iter().enumerate() {
+            for other in &self.bindings[i + 1..] {
+                if let (Some(a), Some(b)) = (&binding.chord, &other.chord) {
+                    if a.overlaps(b) {
+                        conflicts.push((binding, other));
+                    }
+                }
+            }
+        }
+        conflicts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chord::Modifiers;
+    use crate::settings::Shortcuts;
+
+    fn press(key: &str, code: &str, ctrl: bool) -> KeyPress {
+        KeyPress {
+            key: key.into(),
+            code: code.into(),
+            held: Modifiers {
+                ctrl,
+                ..Modifiers::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_default_bindings() {
+        let keymap = Keymap::new(&Settings::default());
+        assert_eq!(
+            keymap.action(&press("p", "KeyP", false)),
+            Some(Action::TogglePause)
+        );
+        // Zhuyin input method on
+        assert_eq!(
+            keymap.action(&press("Process", "KeyF", false)),
+            Some(Action::EnterEdit)
+        );
+        assert_eq!(keymap.action(&press("z", "KeyZ", true)), Some(Action::Undo));
+        assert_eq!(keymap.action(&press("x", "KeyX", false)), None);
+        assert!(keymap.conflicts().is_empty());
+        assert_eq!(keymap.bindings().len(), Action::ALL.len());
+    }
+
+    #[test]
+    fn test_remapped_and_conflicting() {
+        let settings = Settings {
+            shortcuts: Shortcuts {
+                toggle_pause: "Space".into(),
+                redo: "CmdOrCtrl+Z".into(),
+                ..Shortcuts::default()
+            },
+            ..Settings::default()
+        };
+        let keymap = Keymap::new(&settings);
+        assert_eq!(
+            keymap.action(&press(" ", "Space", false)),
+            Some(Action::TogglePause)
+        );
+        assert_eq!(keymap.action(&press("p", "KeyP", false)), None);
+        let conflicts: Vec<_> = keymap
+            .conflicts()
+            .into_iter()
+            .map(|(a, b)| (a.action, b.action))
+            .collect();
+        assert_eq!(conflicts, [(Action::Undo, Action::Redo)]);
+        // The first binding wins
+        assert_eq!(keymap.action(&press("z", "KeyZ", true)), Some(Action::Undo));
+    }
+
+    /// Pairs of actions that clash with `shortcuts`
+    fn clashes(shortcuts: Shortcuts) -> Vec<(Action, Action)> {
+        let settings = Settings {
+            shortcuts,
+            ..Settings::default()
+        };
+        Keymap::new(&settings)
+            .conflicts()
+            .into_iter()
+            .map(|(a, b)| (a.action, b.action))
+            .collect()
+    }
+
+    #[test]
+    fn test_conflicts_as_keys_match() {
+        // A press of `p` matches both, by key and by place
+        assert_eq!(
+            clashes(Shortcuts {
+                enter_edit: "KeyP".into(),
+                ..Shortcuts::default()
+            }),
+            [(Action::TogglePause, Action::EnterEdit)]
+        );
+        // Shift+? matches both, since `?` takes Shift either way
+        assert_eq!(
+            clashes(Shortcuts {
+                grow: "?".into(),
+                shrink: "Shift+?".into(),
+                ..Shortcuts::default()
+            }),
+            [(Action::Grow, Action::Shrink)]
+        );
+    }
+}
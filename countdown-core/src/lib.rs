@@ -0,0 +1,32 @@
+//! Platform-independent countdown logic, shared by the webview UI and the
+//! Tauri backend. Nothing here touches the DOM, a window or a clock: time
+//! comes in through `TimerProvider`.
+
+pub mod announce;
+pub mod chord;
+pub mod config;
+pub mod dial;
+pub mod dispatch;
+pub mod event_logic;
+pub mod history;
+pub mod interval_queue;
+pub mod keymap;
+pub mod settings;
+#[cfg(feature = "serde")]
+pub mod storage;
+pub mod style_utils;
+pub mod theme;
+pub mod time_format;
+pub mod timer_events;
+pub mod timer_logic;
+pub mod timer_provider;
+pub mod timer_service;
+
+pub use settings::{
+    Alerts, Display, EscalationStage, ExpiryCues, ExpiryMotion, Fade, Lock, Progress, ResetGesture,
+    ResetProtection, Settings, SettingsError, Shortcuts, WarningStage,
+};
+pub use theme::{Colors, Theme};
+pub use timer_events::TimerEvent;
+pub use timer_provider::{ManualClockProvider, TimerHandle, TimerProvider};
+pub use timer_service::{TimerCommand, TimerEngine, TimerSnapshot};
@@ -0,0 +1,1194 @@
+//! User-tunable settings, defaulting to the constants in `config`
+
+use crate::chord::{Chord, KeyPress};
+use crate::config::{
+    BLINK_INTERVAL_MS, COUNTDOWN_INTERVAL_MS, INITIAL_SECONDS, MAXIMUM_SECONDS, MINIMUM_SECONDS,
+};
+use crate::keymap::Keymap;
+use crate::theme::{is_css_value, Theme, BUILTIN_THEMES};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Everything a user may change without rebuilding. Missing fields take
+/// their defaults, so a settings file only needs the ones it changes.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct Settings {
+    /// Duration used until one is entered, in seconds
+    pub default_seconds: i32,
+    /// How far past zero the countdown runs before stopping, in seconds
+    pub overrun_limit_seconds: i32,
+    /// Length of one countdown step, in milliseconds
+    pub countdown_interval_ms: u32,
+    /// Half a blink cycle once time is up, in milliseconds
+    pub blink_interval_ms: u32,
+    /// How much more time a snooze gives once time is up, in seconds
+    pub snooze_seconds: i32,
+    /// Name of a built-in theme or one in `themes`
+    pub theme: String,
+    /// User-defined themes, which take precedence over built-ins of the
+    /// same name
+    pub themes: BTreeMap<String, Theme>,
+    pub shortcuts: Shortcuts,
+    pub alerts: Alerts,
+    /// How elapsed time is drawn besides the digits
+    pub progress: Progress,
+    /// How the time itself is drawn
+    pub display: Display,
+    pub fade: Fade,
+    /// How time up is shown
+    pub expiry: ExpiryCues,
+    /// What it takes to reset the time with the mouse
+    pub reset: ResetProtection,
+    /// How a locked timer is unlocked
+    pub lock: Lock,
+    /// Stages of warning as time runs low, in any order
+    pub warnings: Vec<WarningStage>,
+    /// Stages of ever stronger alerts the longer time has been up, in any
+    /// order
+    pub escalation: Vec<EscalationStage>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            default_seconds: INITIAL_SECONDS,
+            overrun_limit_seconds: -MINIMUM_SECONDS,
+            countdown_interval_ms: COUNTDOWN_INTERVAL_MS,
+            blink_interval_ms: BLINK_INTERVAL_MS,
+            snooze_seconds: 300,
+            theme: "dark".into(),
+            themes: BTreeMap::new(),
+            shortcuts: Shortcuts::default(),
+            alerts: Alerts::default(),
+            progress: Progress::default(),
+            display: Display::default(),
+            fade: Fade::default(),
+            expiry: ExpiryCues::default(),
+            reset: ResetProtection::default(),
+            lock: Lock::default(),
+            warnings: WarningStage::defaults(),
+            escalation: EscalationStage::defaults(),
+        }
+    }
+}
+
+/// Keys in the timer window, each a key alone or after modifiers joined by
+/// `+`, see `Chord`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct Shortcuts {
+    pub toggle_pause: String,
+    pub enter_edit: String,
+    /// Switch to the next `Display`
+    pub cycle_display: String,
+    /// Step the window up or down through its preset sizes
+    pub grow: String,
+    pub shrink: String,
+    /// The same as clicking the time
+    pub reset: String,
+    /// More time once time is up, see `Settings::snooze_seconds`
+    pub snooze: String,
+    /// Take back the last reset or newly set duration, and put it back
+    pub undo: String,
+    pub redo: String,
+    pub open_settings: String,
+}
+
+impl Default for Shortcuts {
+    fn default() -> Self {
+        Self {
+            toggle_pause: "p".into(),
+            enter_edit: "f".into(),
+            cycle_display: "d".into(),
+            grow: "=".into(),
+            shrink: "-".into(),
+            reset: "r".into(),
+            snooze: "s".into(),
+            undo: "CmdOrCtrl+z".into(),
+            redo: "CmdOrCtrl+Shift+z".into(),
+            open_settings: "CmdOrCtrl+,".into(),
+        }
+    }
+}
+
+/// What happens besides blinking when time is up
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct Alerts {
+    /// Ask the window manager to flag the window, e.g. bounce the Dock icon
+    pub request_attention: bool,
+    /// Play a short tone
+    pub sound: bool,
+}
+
+/// How prominent the window is: faint while there is plenty of time, and
+/// fully shown on hover, near the end and once time is up
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct Fade {
+    /// Opacity of the whole window while not shown fully; 1 never fades
+    pub idle_opacity: f32,
+    /// Seconds left from which the window is shown fully while running
+    pub reveal_seconds: i32,
+    /// How much the time grows while shown fully near the end; 1 for not at all
+    pub reveal_scale: f32,
+}
+
+impl Default for Fade {
+    fn default() -> Self {
+        Self {
+            idle_opacity: 1.0,
+            reveal_seconds: 120,
+            reveal_scale: 1.0,
+        }
+    }
+}
+
+/// How the time moves once it is up
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum ExpiryMotion {
+    /// `Blink`, or `Pulse` if the system asks for reduced motion
+    #[default]
+    Auto,
+    /// On and off every `blink_interval_ms`
+    Blink,
+    /// A slow, gentle fade in and out
+    Pulse,
+    /// No movement; the other cues mark it
+    Still,
+}
+
+impl ExpiryMotion {
+    pub const ALL: [ExpiryMotion; 4] = [
+        ExpiryMotion::Auto,
+        ExpiryMotion::Blink,
+        ExpiryMotion::Pulse,
+        ExpiryMotion::Still,
+    ];
+
+    /// Its name in the settings file
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExpiryMotion::Auto => "auto",
+            ExpiryMotion::Blink => "blink",
+            ExpiryMotion::Pulse => "pulse",
+            ExpiryMotion::Still => "still",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.as_str() == name)
+    }
+
+    /// The motion to use, `Auto` decided by whether the system asks for
+    /// reduced motion
+    pub fn resolve(self, reduced_motion: bool) -> Self {
+        match self {
+            ExpiryMotion::Auto if reduced_motion => ExpiryMotion::Pulse,
+            ExpiryMotion::Auto => ExpiryMotion::Blink,
+            motion => motion,
+        }
+    }
+}
+
+/// How time up is shown besides its colour, for those who cannot tell the
+/// colours apart or do not want flashing
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct ExpiryCues {
+    pub motion: ExpiryMotion,
+    /// An alarm clock icon before the time
+    pub prefix: bool,
+    /// The backdrop in the expired colour, the time in the backdrop's
+    pub invert: bool,
+    /// A dashed outline around the window
+    pub border: bool,
+}
+
+/// What on the time resets it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum ResetGesture {
+    #[default]
+    Click,
+    DoubleClick,
+    /// Pressing for `hold_ms`, with the progress shown
+    Hold,
+    /// A click with Shift, Ctrl, Alt or Cmd held
+    ModifierClick,
+}
+
+impl ResetGesture {
+    pub const ALL: [ResetGesture; 4] = [
+        ResetGesture::Click,
+        ResetGesture::DoubleClick,
+        ResetGesture::Hold,
+        ResetGesture::ModifierClick,
+    ];
+
+    /// Its name in the settings file
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ResetGesture::Click => "click",
+            ResetGesture::DoubleClick => "double_click",
+            ResetGesture::Hold => "hold",
+            ResetGesture::ModifierClick => "modifier_click",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|g| g.as_str() == name)
+    }
+}
+
+/// Guards against resetting by accident, e.g. while dragging the window
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct ResetProtection {
+    pub gesture: ResetGesture,
+    /// How long `Hold` takes, in milliseconds
+    pub hold_ms: u32,
+    /// Ignore resets, by any gesture or key, until time is up
+    pub only_when_up: bool,
+}
+
+impl Default for ResetProtection {
+    fn default() -> Self {
+        Self {
+            gesture: ResetGesture::Click,
+            hold_ms: 800,
+            only_when_up: false,
+        }
+    }
+}
+
+impl ResetProtection {
+    /// Whether a reset is allowed at all, with time up or not
+    pub fn allows(&self, is_blinking: bool) -> bool {
+        !self.only_when_up || is_blinking
+    }
+
+    /// Whether a click, or with `double` the second of a double-click,
+    /// resets; `Enter` or `Space` on the time counts as a click. `Hold` is
+    /// timed by the window instead.
+    pub fn click_resets(&self, double: bool, modifier: bool) -> bool {
+        match self.gesture {
+            ResetGesture::Click => !double,
+            ResetGesture::DoubleClick => double,
+            ResetGesture::ModifierClick => !double && modifier,
+            ResetGesture::Hold => false,
+        }
+    }
+}
+
+/// Locking the timer ignores clicks, keys and dragging, for sessions that
+/// should not be cut short on a whim
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct Lock {
+    /// Key combination that locks, and unlocks unless there is a passphrase
+    pub chord: String,
+    /// Typed while locked to unlock; empty to unlock with `chord`
+    pub passphrase: String,
+    /// Unlock by itself once time is up
+    pub release_on_expiry: bool,
+}
+
+impl Default for Lock {
+    fn default() -> Self {
+        Self {
+            chord: "CmdOrCtrl+Shift+L".into(),
+            passphrase: String::new(),
+            release_on_expiry: false,
+        }
+    }
+}
+
+impl Lock {
+    /// Whether `press` is the lock chord and so toggles the lock: it always
+    /// locks, but only unlocks without a passphrase
+    pub fn chord_toggles(&self, locked: bool, press: &KeyPress) -> bool {
+        (!locked || self.passphrase.is_empty())
+            && Chord::parse(&self.chord).is_some_and(|chord| chord.matches(press))
+    }
+
+    /// Add `key` to what was typed while locked, keeping only as much as the
+    /// passphrase is long, and say whether it now ends in the passphrase
+    pub fn type_key(&self, typed: &mut String, key: &str) -> bool {
+        if self.passphrase.is_empty() {
+            return false;
+        }
+        // Named keys such as Shift or Backspace are not text
+        if key.chars().count() == 1 {
+            typed.push_str(key);
+        }
+        let keep = self.passphrase.chars().count();
+        let extra = typed.chars().count().saturating_sub(keep);
+        if let Some((at, _)) = typed.char_indices().nth(extra) {
+            typed.drain(..at);
+        }
+        *typed == self.passphrase
+    }
+}
+
+/// A warning as time runs low, from `at_seconds` left until a more urgent
+/// stage or time up
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct WarningStage {
+    pub at_seconds: i32,
+    /// Colour of the time, as any CSS colour; empty for the theme's
+    /// `warning` colour
+    pub color: String,
+    /// Pulse the time slowly
+    pub pulse: bool,
+    /// On reaching the stage, also do what `alerts` says
+    pub alert: bool,
+}
+
+impl Default for WarningStage {
+    fn default() -> Self {
+        Self {
+            at_seconds: 300,
+            color: String::new(),
+            pulse: false,
+            alert: false,
+        }
+    }
+}
+
+impl WarningStage {
+    /// The theme's warning colour at 5:00 left, pulsing from 1:00
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::default(),
+            Self {
+                at_seconds: 60,
+                pulse: true,
+                ..Self::default()
+            },
+        ]
+    }
+}
+
+/// A step up in alerting once time has been up for `after_seconds`. Each
+/// stage lasts until the next one or a reset, which undoes them all.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct EscalationStage {
+    pub after_seconds: i32,
+    /// Half a blink cycle, in milliseconds; 0 keeps the previous stage's
+    pub blink_interval_ms: u32,
+    /// Size of the window relative to before time was up; 1 keeps it
+    pub grow: f32,
+    /// Move the window to the middle of its screen
+    pub center: bool,
+    /// Ask the window manager to flag the window
+    pub request_attention: bool,
+    /// Play a short tone
+    pub sound: bool,
+}
+
+impl Default for EscalationStage {
+    fn default() -> Self {
+        Self {
+            after_seconds: 300,
+            blink_interval_ms: 0,
+            grow: 1.0,
+            center: false,
+            request_attention: false,
+            sound: false,
+        }
+    }
+}
+
+impl EscalationStage {
+    /// Faster blinking 5:00 over; 15:00 over, a larger window too and a
+    /// request for attention
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self {
+                blink_interval_ms: 250,
+                ..Self::default()
+            },
+            Self {
+                after_seconds: 900,
+                grow: 1.5,
+                request_attention: true,
+                ..Self::default()
+            },
+        ]
+    }
+}
+
+/// Every problem found in a set of settings, one per line when displayed
+#[derive(Clone, Debug, PartialEq)]
+pub struct SettingsError {
+    pub problems: Vec<String>,
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.problems.join("\n"))
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+impl Settings {
+    /// The countdown stops here
+    pub fn minimum_seconds(&self) -> i32 {
+        -self.overrun_limit_seconds
+    }
+
+    /// Half a blink cycle with `remaining` seconds left: the latest
+    /// escalation stage's that sets one, or `blink_interval_ms`
+    pub fn blink_interval_at(&self, remaining: i32) -> u32 {
+        self.escalation
+            .iter()
+            .filter(|stage| -remaining >= stage.after_seconds && stage.blink_interval_ms > 0)
+            .max_by_key(|stage| stage.after_seconds)
+            .map_or(self.blink_interval_ms, |stage| stage.blink_interval_ms)
+    }
+
+    /// The theme named by `theme`, or `dark` if there is none by that name
+    pub fn active_theme(&self) -> Theme {
+        self.themes
+            .get(&self.theme)
+            .cloned()
+            .or_else(|| Theme::builtin(&self.theme))
+            .unwrap_or_default()
+    }
+
+    /// Check every field against the range the timer can display and run
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        let mut problems = Vec::new();
+        check_range(
+            &mut problems,
+            "default_seconds",
+            self.default_seconds,
+            0,
+            MAXIMUM_SECONDS,
+            " (00:00 to 59:59)",
+        );
+        check_range(
+            &mut problems,
+            "overrun_limit_seconds",
+            self.overrun_limit_seconds,
+            0,
+            -MINIMUM_SECONDS,
+            " (up to -59:59)",
+        );
+        check_range(
+            &mut problems,
+            "countdown_interval_ms",
+            self.countdown_interval_ms,
+            100,
+            60_000,
+            "",
+        );
+        check_range(
+            &mut problems,
+            "blink_interval_ms",
+            self.blink_interval_ms,
+            50,
+            10_000,
+            "",
+        );
+        check_range(
+            &mut problems,
+            "snooze_seconds",
+            self.snooze_seconds,
+            1,
+            MAXIMUM_SECONDS,
+            " (00:01 to 59:59)",
+        );
+        if !self.themes.contains_key(&self.theme) && Theme::builtin(&self.theme).is_none() {
+            let mut names: Vec<&str> = BUILTIN_THEMES.to_vec();
+            names.extend(self.themes.keys().map(String::as_str));
+            problems.push(format!(
+                "`theme` must be one of {}, not {:?}",
+                names.join(", "),
+                self.theme
+            ));
+        }
+        check_range(
+            &mut problems,
+            "fade.idle_opacity",
+            self.fade.idle_opacity,
+            0.1,
+            1.0,
+            "",
+        );
+        check_range(
+            &mut problems,
+            "fade.reveal_seconds",
+            self.fade.reveal_seconds,
+            0,
+            MAXIMUM_SECONDS,
+            " (00:00 to 59:59)",
+        );
+        check_range(
+            &mut problems,
+            "fade.reveal_scale",
+            self.fade.reveal_scale,
+            1.0,
+            2.0,
+            "",
+        );
+        check_range(
+            &mut problems,
+            "reset.hold_ms",
+            self.reset.hold_ms,
+            200,
+            5_000,
+            "",
+        );
+        for (i, stage) in self.warnings.iter().enumerate() {
+            check_range(
+                &mut problems,
+                &format!("warnings[{i}].at_seconds"),
+                stage.at_seconds,
+                1,
+                MAXIMUM_SECONDS,
+                " (00:01 to 59:59)",
+            );
+            if !stage.color.is_empty() && !is_css_value(&stage.color) {
+                problems.push(format!(
+                    "`warnings[{i}].color` must be a CSS colour or empty, not {:?}",
+                    stage.color
+                ));
+            }
+            if self.warnings[..i]
+                .iter()
+                .any(|other| other.at_seconds == stage.at_seconds)
+            {
+                problems.push(format!(
+                    "`warnings[{i}].at_seconds` repeats an earlier stage's {}",
+                    stage.at_seconds
+                ));
+            }
+        }
+        for (i, stage) in self.escalation.iter().enumerate() {
+            check_range(
+                &mut problems,
+                &format!("escalation[{i}].after_seconds"),
+                stage.after_seconds,
+                1,
+                -MINIMUM_SECONDS,
+                " (up to 59:59)",
+            );
+            if stage.blink_interval_ms != 0 {
+                check_range(
+                    &mut problems,
+                    &format!("escalation[{i}].blink_interval_ms"),
+                    stage.blink_interval_ms,
+                    50,
+                    10_000,
+                    " or 0",
+                );
+            }
+            check_range(
+                &mut problems,
+                &format!("escalation[{i}].grow"),
+                stage.grow,
+                1.0,
+                4.0,
+                "",
+            );
+            if self.escalation[..i]
+                .iter()
+                .any(|other| other.after_seconds == stage.after_seconds)
+            {
+                problems.push(format!(
+                    "`escalation[{i}].after_seconds` repeats an earlier stage's {}",
+                    stage.after_seconds
+                ));
+            }
+        }
+        for (name, theme) in &self.themes {
+            problems.extend(theme.problems(&format!("themes.{name}")));
+        }
+        let keymap = Keymap::new(self);
+        for binding in keymap.bindings() {
+            let name = binding.action.setting();
+            match &binding.chord {
+                None => problems.push(format!(
+                    "`{name}` must be a key, or modifiers and a key joined by +, not {:?}",
+                    binding.text
+                )),
+                Some(chord)
+                    if !chord.has_modifiers()
+                        && RESERVED_KEYS
+                            .iter()
+                            .any(|key| chord.key.eq_ignore_ascii_case(key)) =>
+                {
+                    problems.push(format!(
+                        "`{name}` must be a key other than {}, not {:?}",
+                        RESERVED_KEYS.join(", "),
+                        binding.text
+                    ))
+                }
+                Some(_) => {}
+            }
+        }
+        for (binding, other) in keymap.conflicts() {
+            let (name, other_name) = (binding.action.setting(), other.action.setting());
+            if binding.text == other.text {
+                problems.push(format!(
+                    "`{name}` and `{other_name}` are both {:?}",
+                    binding.text
+                ));
+            } else {
+                problems.push(format!(
+                    "`{name}` {:?} and `{other_name}` {:?} are the same keys",
+                    binding.text, other.text
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(SettingsError { problems })
+        }
+    }
+}
+
+/// Progress drawn behind or around the time
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Progress {
+    #[default]
+    None,
+    /// An outline around the window that fills clockwise
+    Ring,
+    /// A thin bar along the bottom
+    Bar,
+    /// The backdrop fills from left to right
+    Fill,
+}
+
+impl Progress {
+    pub const ALL: [Progress; 4] = [
+        Progress::None,
+        Progress::Ring,
+        Progress::Bar,
+        Progress::Fill,
+    ];
+
+    /// Its name in the settings file
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Progress::None => "none",
+            Progress::Ring => "ring",
+            Progress::Bar => "bar",
+            Progress::Fill => "fill",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.as_str() == name)
+    }
+}
+
+/// How the time is drawn
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Display {
+    /// `MM:SS`
+    #[default]
+    Digits,
+    /// A disc on a 60-minute face that shrinks as time passes
+    Dial,
+    /// Digits on split-flap cards
+    Flip,
+}
+
+impl Display {
+    pub const ALL: [Display; 3] = [Display::Digits, Display::Dial, Display::Flip];
+
+    /// Its name in the settings file
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Display::Digits => "digits",
+            Display::Dial => "dial",
+            Display::Flip => "flip",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.as_str() == name)
+    }
+
+    /// The one after this, wrapping around
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&d| d == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+/// Keys time-entry mode needs for itself
+const RESERVED_KEYS: [&str; 2] = ["Enter", "Escape"];
+
+fn check_range<T: PartialOrd + fmt::Display>(
+    problems: &mut Vec<String>,
+    name: &str,
+    value: T,
+    min: T,
+    max: T,
+    hint: &str,
+) {
+    if value < min || value > max {
+        problems.push(format!(
+            "`{name}` must be between {min} and {max}{hint}, not {value}"
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chord::Modifiers;
+
+    #[test]
+    fn test_defaults_match_config() {
+        let settings = Settings::default();
+        assert_eq!(settings.default_seconds, INITIAL_SECONDS);
+        assert_eq!(settings.minimum_seconds(), MINIMUM_SECONDS);
+        assert_eq!(settings.countdown_interval_ms, COUNTDOWN_INTERVAL_MS);
+        assert_eq!(settings.blink_interval_ms, BLINK_INTERVAL_MS);
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_reports_every_problem() {
+        let settings = Settings {
+            default_seconds: 3600,
+            blink_interval_ms: 0,
+            ..Settings::default()
+        };
+        let error = settings.validate().unwrap_err();
+        assert_eq!(
+            error.problems,
+            [
+                "`default_seconds` must be between 0 and 3599 (00:00 to 59:59), not 3600",
+                "`blink_interval_ms` must be between 50 and 10000, not 0",
+            ]
+        );
+        assert_eq!(error.to_string().lines().count(), 2);
+    }
+
+    #[test]
+    fn test_validate_bounds_are_inclusive() {
+        let settings = Settings {
+            default_seconds: 0,
+            overrun_limit_seconds: 3599,
+            countdown_interval_ms: 100,
+            blink_interval_ms: 10_000,
+            ..Settings::default()
+        };
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_theme_and_shortcuts() {
+        let settings = Settings {
+            theme: "solarized".into(),
+            themes: BTreeMap::from([(
+                "mine".into(),
+                Theme {
+                    corner_radius_px: 99,
+                    ..Theme::light()
+                },
+            )]),
+            shortcuts: Shortcuts {
+                toggle_pause: "Enter".into(),
+                enter_edit: "Enter".into(),
+                ..Shortcuts::default()
+            },
+            ..Settings::default()
+        };
+        let error = settings.validate().unwrap_err();
+        assert_eq!(
+            error.problems,
+            [
+                "`theme` must be one of dark, light, high-contrast, minimal, mine, not \"solarized\"",
+                "`themes.mine.corner_radius_px` must be between 0 and 50, not 99",
+                "`shortcuts.toggle_pause` must be a key other than Enter, Escape, not \"Enter\"",
+                "`shortcuts.enter_edit` must be a key other than Enter, Escape, not \"Enter\"",
+                "`shortcuts.toggle_pause` and `shortcuts.enter_edit` are both \"Enter\"",
+            ]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_partial_file_takes_defaults() {
+        let settings: Settings = serde_json::from_str(r#"{"default_seconds": 600}"#).unwrap();
+        assert_eq!(
+            settings,
+            Settings {
+                default_seconds: 600,
+                ..Settings::default()
+            }
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_partial_section_takes_defaults() {
+        let settings: Settings = serde_json::from_str(r#"{"alerts": {"sound": true}}"#).unwrap();
+        assert_eq!(
+            settings.alerts,
+            Alerts {
+                request_attention: false,
+                sound: true,
+            }
+        );
+        assert_eq!(settings.active_theme(), Theme::dark());
+    }
+
+    #[test]
+    fn test_active_theme() {
+        let mut settings = Settings {
+            theme: "light".into(),
+            ..Settings::default()
+        };
+        assert_eq!(settings.active_theme(), Theme::light());
+
+        // A user theme may replace a built-in one
+        let mine = Theme {
+            font_size_em: 3.0,
+            ..Theme::light()
+        };
+        settings.themes.insert("light".into(), mine.clone());
+        assert_eq!(settings.active_theme(), mine);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_user_theme_extends_dark() {
+        let settings: Settings =
+            serde_json::from_str(r#"{"theme": "big", "themes": {"big": {"font_size_em": 4}}}"#)
+                .unwrap();
+        assert!(settings.validate().is_ok());
+        assert_eq!(
+            settings.active_theme(),
+            Theme {
+                font_size_em: 4.0,
+                ..Theme::dark()
+            }
+        );
+    }
+
+    #[test]
+    fn test_progress_names() {
+        for progress in Progress::ALL {
+            assert_eq!(Progress::from_name(progress.as_str()), Some(progress));
+        }
+        assert_eq!(Progress::from_name("pie"), None);
+    }
+
+    #[test]
+    fn test_expiry_motion_follows_reduced_motion() {
+        assert_eq!(ExpiryMotion::Auto.resolve(false), ExpiryMotion::Blink);
+        assert_eq!(ExpiryMotion::Auto.resolve(true), ExpiryMotion::Pulse);
+        // A choice made in the settings stands
+        assert_eq!(ExpiryMotion::Blink.resolve(true), ExpiryMotion::Blink);
+        assert_eq!(ExpiryMotion::Still.resolve(false), ExpiryMotion::Still);
+        for motion in ExpiryMotion::ALL {
+            assert_eq!(ExpiryMotion::from_name(motion.as_str()), Some(motion));
+        }
+    }
+
+    #[test]
+    fn test_reset_gestures() {
+        let protection = |gesture| ResetProtection {
+            gesture,
+            ..ResetProtection::default()
+        };
+        let click = protection(ResetGesture::Click);
+        assert!(click.click_resets(false, false));
+        // A double-click's second click alone does not reset twice
+        assert!(!click.click_resets(true, false));
+
+        let double = protection(ResetGesture::DoubleClick);
+        assert!(!double.click_resets(false, false));
+        assert!(double.click_resets(true, false));
+
+        let modifier = protection(ResetGesture::ModifierClick);
+        assert!(!modifier.click_resets(false, false));
+        assert!(modifier.click_resets(false, true));
+
+        assert!(!protection(ResetGesture::Hold).click_resets(false, true));
+        for gesture in ResetGesture::ALL {
+            assert_eq!(ResetGesture::from_name(gesture.as_str()), Some(gesture));
+        }
+    }
+
+    #[test]
+    fn test_reset_only_when_up() {
+        let protection = ResetProtection {
+            only_when_up: true,
+            ..ResetProtection::default()
+        };
+        assert!(!protection.allows(false));
+        assert!(protection.allows(true));
+        assert!(ResetProtection::default().allows(false));
+    }
+
+    #[test]
+    fn test_lock_chord_and_passphrase() {
+        let press = |key: &str, ctrl| KeyPress {
+            key: key.into(),
+            code: format!("Key{}", key.to_uppercase()),
+            held: Modifiers {
+                ctrl,
+                shift: ctrl,
+                ..Modifiers::default()
+            },
+        };
+        let lock = Lock::default();
+        assert!(lock.chord_toggles(false, &press("L", true)));
+        assert!(lock.chord_toggles(true, &press("L", true)));
+        assert!(!lock.chord_toggles(true, &press("p", false)));
+
+        let lock = Lock {
+            passphrase: "focus".into(),
+            ..Lock::default()
+        };
+        // Only the passphrase unlocks
+        assert!(lock.chord_toggles(false, &press("L", true)));
+        assert!(!lock.chord_toggles(true, &press("L", true)));
+        let mut typed = String::new();
+        let unlocked: Vec<bool> = ["x", "f", "o", "Shift", "c", "u", "s"]
+            .iter()
+            .map(|key| lock.type_key(&mut typed, key))
+            .collect();
+        assert_eq!(unlocked.last(), Some(&true));
+        assert_eq!(unlocked.iter().filter(|done| **done).count(), 1);
+        assert_eq!(typed, "focus");
+
+        let settings = Settings {
+            lock: Lock {
+                chord: "Hyper+L".into(),
+                ..Lock::default()
+            },
+            ..Settings::default()
+        };
+        assert_eq!(settings.validate().unwrap_err().problems.len(), 1);
+    }
+
+    #[test]
+    fn test_display_cycles_through_all() {
+        assert_eq!(Display::Digits.next(), Display::Dial);
+        assert_eq!(Display::Dial.next(), Display::Flip);
+        assert_eq!(Display::Flip.next(), Display::Digits);
+        for display in Display::ALL {
+            assert_eq!(Display::from_name(display.as_str()), Some(display));
+        }
+    }
+
+    #[test]
+    fn test_warning_stages_checked() {
+        let settings = Settings {
+            warnings: vec![
+                WarningStage {
+                    at_seconds: 0,
+                    ..WarningStage::default()
+                },
+                WarningStage {
+                    color: "red; font-size: 9em".into(),
+                    ..WarningStage::default()
+                },
+                WarningStage::default(),
+            ],
+            ..Settings::default()
+        };
+        assert_eq!(
+            settings.validate().unwrap_err().problems,
+            [
+                "`warnings[0].at_seconds` must be between 1 and 3599 (00:01 to 59:59), not 0",
+                "`warnings[1].color` must be a CSS colour or empty, not \"red; font-size: 9em\"",
+                "`warnings[2].at_seconds` repeats an earlier stage's 300",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escalation_stages_checked() {
+        let settings = Settings {
+            escalation: vec![
+                EscalationStage {
+                    after_seconds: 0,
+                    blink_interval_ms: 10,
+                    grow: 0.5,
+                    ..EscalationStage::default()
+                },
+                EscalationStage::default(),
+                EscalationStage::default(),
+            ],
+            ..Settings::default()
+        };
+        assert_eq!(
+            settings.validate().unwrap_err().problems,
+            [
+                "`escalation[0].after_seconds` must be between 1 and 3599 (up to 59:59), not 0",
+                "`escalation[0].blink_interval_ms` must be between 50 and 10000 or 0, not 10",
+                "`escalation[0].grow` must be between 1 and 4, not 0.5",
+                "`escalation[2].after_seconds` repeats an earlier stage's 300",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_blink_interval_escalates() {
+        let settings = Settings {
+            blink_interval_ms: 500,
+            escalation: vec![
+                EscalationStage {
+                    after_seconds: 600,
+                    blink_interval_ms: 100,
+                    ..EscalationStage::default()
+                },
+                EscalationStage {
+                    after_seconds: 60,
+                    blink_interval_ms: 250,
+                    ..EscalationStage::default()
+                },
+                // Keeps the previous stage's
+                EscalationStage {
+                    after_seconds: 900,
+                    ..EscalationStage::default()
+                },
+            ],
+            ..Settings::default()
+        };
+        assert_eq!(settings.blink_interval_at(-59), 500);
+        assert_eq!(settings.blink_interval_at(-60), 250);
+        assert_eq!(settings.blink_interval_at(-600), 100);
+        assert_eq!(settings.blink_interval_at(-1000), 100);
+        assert_eq!(settings.blink_interval_at(30), 500);
+    }
+
+    #[test]
+    fn test_fade_ranges() {
+        let settings = Settings {
+            fade: Fade {
+                idle_opacity: 0.0,
+                reveal_seconds: -1,
+                reveal_scale: 3.0,
+            },
+            ..Settings::default()
+        };
+        assert_eq!(
+            settings.validate().unwrap_err().problems,
+            [
+                "`fade.idle_opacity` must be between 0.1 and 1, not 0",
+                "`fade.reveal_seconds` must be between 0 and 3599 (00:00 to 59:59), not -1",
+                "`fade.reveal_scale` must be between 1 and 2, not 3",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shortcuts_must_differ() {
+        let settings = Settings {
+            shortcuts: Shortcuts {
+                cycle_display: "p".into(),
+                ..Shortcuts::default()
+            },
+            ..Settings::default()
+        };
+        assert_eq!(
+            settings.validate().unwrap_err().problems,
+            ["`shortcuts.toggle_pause` and `shortcuts.cycle_display` are both \"p\""]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_progress_wire_names_match() {
+        for progress in Progress::ALL {
+            let json = serde_json::to_string(&progress).unwrap();
+            assert_eq!(json, format!("\"{}\"", progress.as_str()));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_expiry_cues_from_json() {
+        let settings: Settings =
+            serde_json::from_str(r#"{"expiry": {"motion": "still", "border": true}}"#).unwrap();
+        assert_eq!(
+            settings.expiry,
+            ExpiryCues {
+                motion: ExpiryMotion::Still,
+                border: true,
+                ..ExpiryCues::default()
+            }
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_unknown_field_is_an_error() {
+        let error = serde_json::from_str::<Settings>(r#"{"blink_ms": 600}"#).unwrap_err();
+        assert!(error.to_string().contains("unknown field `blink_ms`"));
+    }
+}
@@ -0,0 +1,319 @@
+//! Everything remembered between runs, as one versioned document kept in
+//! whatever `Storage` the caller has: LocalStorage in the webview, files in
+//! the backend, memory in tests
+
+use crate::config::MAXIMUM_SECONDS;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Key the document is stored under
+pub const STATE_KEY: &str = "countdown_state";
+/// Where an unreadable document, or legacy value, is kept aside; later
+/// ones go under `backup_key(2)` and on, so none is lost
+pub const BACKUP_KEY: &str = "countdown_state_backup";
+/// Before the document: the set duration alone, as a bare number
+pub const LEGACY_DURATION_KEY: &str = "countdown_timer_remaining_seconds";
+/// The document's current layout
+pub const STATE_VERSION: u32 = 1;
+
+/// Text stored by key
+pub trait Storage {
+    /// What is stored under `key`, if anything
+    fn read(&self, key: &str) -> Result<Option<String>, StorageError>;
+    fn write(&self, key: &str, value: &str) -> Result<(), StorageError>;
+    fn remove(&self, key: &str) -> Result<(), StorageError>;
+}
+
+/// Why a storage could not be read or written
+#[derive(Clone, Debug, PartialEq)]
+pub struct StorageError {
+    pub message: String,
+}
+
+impl StorageError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+/// Kept only as long as it lives
+#[derive(Default)]
+pub struct MemoryStorage {
+    items: RefCell<BTreeMap<String, String>>,
+}
+
+impl Storage for MemoryStorage {
+    fn read(&self, key: &str) -> Result<Option<String>, StorageError> {
+        Ok(self.items.borrow().get(key).cloned())
+    }
+
+    fn write(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        self.items.borrow_mut().insert(key.into(), value.into());
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<(), StorageError> {
+        self.items.borrow_mut().remove(key);
+        Ok(())
+    }
+}
+
+/// The document. Fields added later default when missing, so older
+/// documents still load.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct StoredState {
+    pub version: u32,
+    /// The most recently set duration, in seconds
+    pub duration_seconds: Option<i32>,
+    /// Whether the timer was locked, see `Lock`
+    pub locked: bool,
+}
+
+impl Default for StoredState {
+    fn default() -> Self {
+        Self {
+            version: STATE_VERSION,
+            duration_seconds: None,
+            locked: false,
+        }
+    }
+}
+
+impl StoredState {
+    /// `duration_seconds` if it is one the timer accepts
+    pub fn duration(&self) -> Option<i32> {
+        self.duration_seconds
+            .filter(|seconds| (0..=MAXIMUM_SECONDS).contains(seconds))
+    }
+}
+
+/// What `load` found
+#[derive(Clone, Debug, PartialEq)]
+pub struct Loaded {
+    pub state: StoredState,
+    /// Set when what was stored could not be read and was moved to a
+    /// backup key, saying why
+    pub backed_up: Option<String>,
+}
+
+/// The stored document; failing that, one migrated from the legacy key;
+/// failing that, an empty one. Anything unreadable is kept under a backup
+/// key rather than lost, and replaced by an empty document.
+pub fn load(storage: &dyn Storage) -> Result<Loaded, StorageError> {
+    if let Some(text) = storage.read(STATE_KEY)? {
+        return match parse(&text) {
+            Ok(state) => Ok(Loaded {
+                state,
+                backed_up: None,
+            }),
+            Err(problem) => back_up(storage, &text, problem),
+        };
+    }
+    let Some(text) = storage.read(LEGACY_DURATION_KEY)? else {
+        return Ok(Loaded {
+            state: StoredState::default(),
+            backed_up: None,
+        });
+    };
+    let Ok(seconds) = text.trim().parse::<i32>() else {
+        let loaded = back_up(storage, &text, format!("not a duration: {text:?}"))?;
+        storage.remove(LEGACY_DURATION_KEY)?;
+        return Ok(loaded);
+    };
+    let state = StoredState {
+        duration_seconds: Some(seconds),
+        ..StoredState::default()
+    };
+    save(storage, &state)?;
+    storage.remove(LEGACY_DURATION_KEY)?;
+    Ok(Loaded {
+        state,
+        backed_up: None,
+    })
+}
+
+pub fn save(storage: &dyn Storage, state: &StoredState) -> Result<(), StorageError> {
+    let text = serde_json::to_string(state).map_err(|err| StorageError::new(err.to_string()))?;
+    storage.write(STATE_KEY, &text)
+}
+
+/// A document of this or an earlier version
+fn parse(text: &str) -> Result<StoredState, String> {
+    let state: StoredState = serde_json::from_str(text).map_err(|err| err.to_string())?;
+    if state.version > STATE_VERSION {
+        return Err(format!(
+            "version {} is newer than this app's {}",
+            state.version, STATE_VERSION
+        ));
+    }
+    // Version 1 is the first; later versions migrate from here
+    Ok(StoredState {
+        version: STATE_VERSION,
+        ..state
+    })
+}
+
+/// `BACKUP_KEY` for the first backup, numbered keys after it
+pub fn backup_key(number: u32) -> String {
+    match number {
+        0 | 1 => BACKUP_KEY.to_string(),
+        number => format!("{BACKUP_KEY}_{number}"),
+    }
+}
+
+/// Keep `text` under the first free backup key, unless one already holds
+/// it, then start afresh so it is not read and kept again
+fn back_up(storage: &dyn Storage, text: &str, problem: String) -> Result<Loaded, StorageError> {
+    for number in 1.. {
+        let key = backup_key(number);
+        match storage.read(&key)? {
+            Some(kept) if kept == text => break,
+            Some(_) => continue,
+            None => {
+                storage.write(&key, text)?;
+                break;
+            }
+        }
+    }
+    let state = StoredState::default();
+    save(storage, &state)?;
+    Ok(Loaded {
+        state,
+        backed_up: Some(problem),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_storage_loads_empty_state() {
+        let storage = MemoryStorage::default();
+        let loaded = load(&storage).unwrap();
+        assert_eq!(loaded.state, StoredState::default());
+        assert_eq!(loaded.backed_up, None);
+        // Nothing is written until something is saved
+        assert_eq!(storage.read(STATE_KEY).unwrap(), None);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let storage = MemoryStorage::default();
+        let state = StoredState {
+            duration_seconds: Some(600),
+            locked: true,
+            ..StoredState::default()
+        };
+        save(&storage, &state).unwrap();
+        assert_eq!(load(&storage).unwrap().state, state);
+        assert_eq!(
+            storage.read(STATE_KEY).unwrap().unwrap(),
+            r#"{"version":1,"duration_seconds":600,"locked":true}"#
+        );
+
+        // A document from before `locked` still loads
+        storage
+            .write(STATE_KEY, r#"{"version":1,"duration_seconds":600}"#)
+            .unwrap();
+        assert!(!load(&storage).unwrap().state.locked);
+    }
+
+    #[test]
+    fn test_migrates_legacy_duration() {
+        let storage = MemoryStorage::default();
+        storage.write(LEGACY_DURATION_KEY, "900").unwrap();
+        let loaded = load(&storage).unwrap();
+        assert_eq!(loaded.state.duration(), Some(900));
+        assert_eq!(storage.read(LEGACY_DURATION_KEY).unwrap(), None);
+        // Migrated once: the document is read from now on
+        assert_eq!(load(&storage).unwrap(), loaded);
+    }
+
+    #[test]
+    fn test_corrupt_document_is_backed_up() {
+        let storage = MemoryStorage::default();
+        storage.write(STATE_KEY, "{not json").unwrap();
+        let loaded = load(&storage).unwrap();
+        assert_eq!(loaded.state, StoredState::default());
+        assert!(loaded.backed_up.is_some());
+        assert_eq!(
+            storage.read(BACKUP_KEY).unwrap().as_deref(),
+            Some("{not json")
+        );
+
+        let storage = MemoryStorage::default();
+        storage.write(LEGACY_DURATION_KEY, "ten minutes").unwrap();
+        let loaded = load(&storage).unwrap();
+        assert_eq!(loaded.state.duration(), None);
+        assert_eq!(
+            storage.read(BACKUP_KEY).unwrap().as_deref(),
+            Some("ten minutes")
+        );
+        assert_eq!(storage.read(LEGACY_DURATION_KEY).unwrap(), None);
+    }
+
+    #[test]
+    fn test_successive_corruptions_keep_every_backup() {
+        let storage = MemoryStorage::default();
+        storage.write(STATE_KEY, "{not json").unwrap();
+        assert!(load(&storage).unwrap().backed_up.is_some());
+        // Replaced, so loading again neither complains nor backs up again
+        assert_eq!(load(&storage).unwrap().backed_up, None);
+        assert_eq!(storage.read(&backup_key(2)).unwrap(), None);
+
+        storage.write(STATE_KEY, "{also not json").unwrap();
+        assert!(load(&storage).unwrap().backed_up.is_some());
+        assert_eq!(
+            storage.read(BACKUP_KEY).unwrap().as_deref(),
+            Some("{not json")
+        );
+        assert_eq!(
+            storage.read(&backup_key(2)).unwrap().as_deref(),
+            Some("{also not json")
+        );
+        assert_eq!(load(&storage).unwrap().state, StoredState::default());
+
+        // The same text again is already kept
+        storage.write(STATE_KEY, "{not json").unwrap();
+        load(&storage).unwrap();
+        assert_eq!(storage.read(&backup_key(3)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_newer_version_is_kept_aside() {
+        let storage = MemoryStorage::default();
+        storage
+            .write(STATE_KEY, r#"{"version":2,"duration_seconds":60}"#)
+            .unwrap();
+        let loaded = load(&storage).unwrap();
+        assert_eq!(loaded.state.duration(), None);
+        assert!(loaded.backed_up.unwrap().contains("newer"));
+    }
+
+    #[test]
+    fn test_out_of_range_duration_is_ignored() {
+        let state = StoredState {
+            duration_seconds: Some(-5),
+            ..StoredState::default()
+        };
+        assert_eq!(state.duration(), None);
+        let state = StoredState {
+            duration_seconds: Some(MAXIMUM_SECONDS),
+            ..StoredState::default()
+        };
+        assert_eq!(state.duration(), Some(MAXIMUM_SECONDS));
+    }
+}
@@ -0,0 +1,443 @@
+use crate::settings::{ExpiryCues, ExpiryMotion, Fade, Progress, WarningStage};
+use crate::theme::{parse_hex_color, Colors, Theme};
+
+/// Compute CSS styles for the timer display based on blinking and visibility
+/// state, the warning stage reached, if any, and how time up is shown, its
+/// motion already resolved
+pub fn compute_timer_style(
+    is_blinking: bool,
+    is_visible: bool,
+    is_paused: bool,
+    warning: Option<&WarningStage>,
+    cues: &ExpiryCues,
+    colors: &Colors,
+) -> String {
+    // Base style: pointer cursor and disable text selection
+    let base: &str = "cursor: pointer; user-select: none;";
+    // On the expired backdrop when inverted, see compute_expiry_style
+    let color = if is_blinking && cues.invert {
+        "var(--backdrop-color)"
+    } else {
+        state_color(is_blinking, is_paused, warning, colors)
+    };
+    let opacity = blink_opacity(is_blinking, is_visible);
+    // The keyframes are in styles.css
+    let pulse = match warning {
+        _ if is_paused => "",
+        _ if is_blinking && cues.motion == ExpiryMotion::Pulse => {
+            " animation: expiry-pulse 3s ease-in-out infinite;"
+        }
+        Some(stage) if stage.pulse => " animation: warning-pulse 2s ease-in-out infinite;",
+        _ => "",
+    };
+    format!("{} color: {}; opacity: {};{}", base, color, opacity, pulse)
+}
+
+/// Whether the time is shown in the blink's current phase: always unless
+/// `motion` is blinking
+pub fn blink_visible(is_visible: bool, motion: ExpiryMotion) -> bool {
+    is_visible || !matches!(motion, ExpiryMotion::Blink | ExpiryMotion::Auto)
+}
+
+/// Expired when blinking, paused when paused, the warning stage's colour
+/// when one is reached, running otherwise
+fn state_color<'a>(
+    is_blinking: bool,
+    is_paused: bool,
+    warning: Option<&'a WarningStage>,
+    colors: &'a Colors,
+) -> &'a str {
+    if is_blinking {
+        &colors.expired
+    } else if is_paused {
+        &colors.paused
+    } else if let Some(stage) = warning {
+        if stage.color.is_empty() {
+            &colors.warning
+        } else {
+            &stage.color
+        }
+    } else {
+        &colors.running
+    }
+}
+
+/// Invisible only during blink off, but still clickable
+fn blink_opacity(is_blinking: bool, is_visible: bool) -> f32 {
+    if is_blinking && !is_visible {
+        0.0
+    } else {
+        1.0
+    }
+}
+
+/// How much of the set duration has passed, from 0 to 1; 1 once time is up
+pub fn progress_fraction(remaining: i32, reset_time: i32) -> f64 {
+    if remaining <= 0 || reset_time <= 0 {
+        return 1.0;
+    }
+    (1.0 - f64::from(remaining) / f64::from(reset_time)).clamp(0.0, 1.0)
+}
+
+/// CSS for the progress element, in the same colour as the time and
+/// blinking with it
+pub fn compute_progress_style(
+    progress: Progress,
+    fraction: f64,
+    is_blinking: bool,
+    is_visible: bool,
+    is_paused: bool,
+    warning: Option<&WarningStage>,
+    colors: &Colors,
+) -> String {
+    let color = state_color(is_blinking, is_paused, warning, colors);
+    let opacity = blink_opacity(is_blinking, is_visible);
+    let percent = fraction * 100.0;
+    match progress {
+        Progress::None => String::new(),
+        // The ring's path length is normalised to 100
+        Progress::Ring => format!(
+            "stroke: {}; stroke-dasharray: {:.1} 100; opacity: {};",
+            color, percent, opacity
+        ),
+        Progress::Bar => format!(
+            "width: {:.1}%; background-color: {}; opacity: {};",
+            percent, color, opacity
+        ),
+        // Faint, so the digits stay readable on top
+        Progress::Fill => format!(
+            "width: {:.1}%; background-color: {}; opacity: {};",
+            percent,
+            color,
+            opacity * 0.25
+        ),
+    }
+}
+
+/// CSS for the window's backdrop. Its colour is also `--backdrop-color`,
+/// for the time when inverted.
+pub fn compute_container_style(theme: &Theme) -> String {
+    let (r, g, b) = parse_hex_color(&theme.background).unwrap_or((0, 0, 0));
+    format!(
+        "background-color: rgba({}, {}, {}, {}); border-radius: {}px; --backdrop-color: {};",
+        r, g, b, theme.background_opacity, theme.corner_radius_px, theme.background
+    )
+}
+
+/// CSS for the window once time is up, after the container's: the backdrop
+/// in the expired colour when inverted, and a dashed outline
+pub fn compute_expiry_style(cues: &ExpiryCues, is_blinking: bool, colors: &Colors) -> String {
+    if !is_blinking {
+        return String::new();
+    }
+    let mut style = String::new();
+    if cues.invert {
+        style.push_str(&format!("background-color: {};", colors.expired));
+    }
+    if cues.border {
+        let color = if cues.invert {
+            "var(--backdrop-color)"
+        } else {
+            &colors.expired
+        };
+        style.push_str(&format!(
+            " outline: 0.2em dashed {}; outline-offset: -0.2em;",
+            color
+        ));
+    }
+    style.trim_start().to_string()
+}
+
+/// Whether the window is shown fully whatever `fade.idle_opacity` says:
+/// once time is up, and over the last `fade.reveal_seconds` while running
+pub fn is_revealed(fade: &Fade, remaining: i32, is_blinking: bool, is_paused: bool) -> bool {
+    is_blinking || (!is_paused && remaining <= fade.reveal_seconds)
+}
+
+/// CSS variables for the window's fade and the time's enlargement, which
+/// `styles.css` applies. Hovering shows the window fully too, in CSS.
+pub fn compute_fade_style(fade: &Fade, revealed: bool) -> String {
+    let (opacity, scale) = if revealed {
+        (1.0, fade.reveal_scale)
+    } else {
+        (fade.idle_opacity, 1.0)
+    };
+    format!("--idle-opacity: {}; --reveal-scale: {};", opacity, scale)
+}
+
+/// CSS for the time, shown or being entered: font and readability glow
+pub fn compute_text_style(theme: &Theme) -> String {
+    let shadow = if theme.text_shadow.is_empty() {
+        "none".to_string()
+    } else {
+        // Stacked for a glow strong enough to read on any desktop
+        vec![format!("0 0 5px {}", theme.text_shadow); 4].join(", ")
+    };
+    format!(
+        "font-family: {}; font-size: {}em; text-shadow: {};",
+        theme.font_family, theme.font_size_em, shadow
+    )
+}
+
+/// CSS for the time-entry field
+pub fn compute_input_style(theme: &Theme) -> String {
+    format!(
+        "{} color: {};",
+        compute_text_style(theme),
+        theme.colors.editing
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cues() -> ExpiryCues {
+        ExpiryCues::default()
+    }
+
+    #[test]
+    fn test_default_style() {
+        let style = compute_timer_style(false, true, false, None, &cues(), &Colors::default());
+        assert!(style.contains("cursor: pointer;"));
+        assert!(style.contains("user-select: none;"));
+        assert!(style.contains("color: white;"));
+        assert!(style.contains("opacity: 1;"));
+    }
+
+    #[test]
+    fn test_blink_visible() {
+        let style = compute_timer_style(true, true, false, None, &cues(), &Colors::default());
+        assert!(style.contains("color: red;"));
+        assert!(style.contains("opacity: 1;"));
+    }
+
+    #[test]
+    fn test_blink_hidden() {
+        let style = compute_timer_style(true, false, false, None, &cues(), &Colors::default());
+        assert!(style.contains("color: red;"));
+        assert!(style.contains("opacity: 0;"));
+    }
+
+    #[test]
+    fn test_paused_style() {
+        let style = compute_timer_style(false, true, true, None, &cues(), &Colors::default());
+        assert!(style.contains("color: darkturquoise;"));
+        assert!(style.contains("opacity: 1;"));
+    }
+
+    #[test]
+    fn test_custom_colors() {
+        let colors = Colors {
+            running: "#eeeeee".into(),
+            paused: "gray".into(),
+            expired: "orange".into(),
+            ..Colors::default()
+        };
+        assert!(
+            compute_timer_style(false, true, false, None, &cues(), &colors)
+                .contains("color: #eeeeee;")
+        );
+        assert!(
+            compute_timer_style(false, true, true, None, &cues(), &colors).contains("color: gray;")
+        );
+        assert!(
+            compute_timer_style(true, true, false, None, &cues(), &colors)
+                .contains("color: orange;")
+        );
+    }
+
+    #[test]
+    fn test_pausing_in_blinking() {
+        let style = compute_timer_style(true, true, true, None, &cues(), &Colors::default());
+        assert!(style.contains("color: red;"));
+        assert!(style.contains("opacity: 1;"));
+    }
+
+    #[test]
+    fn test_container_style() {
+        let style = compute_container_style(&Theme::dark());
+        assert!(style.contains("background-color: rgba(0, 0, 0, 0.2);"));
+        assert!(style.contains("border-radius: 8px;"));
+
+        let style = compute_container_style(&Theme::light());
+        assert!(style.contains("background-color: rgba(255, 255, 255, 0.6);"));
+    }
+
+    #[test]
+    fn test_text_style() {
+        let style = compute_text_style(&Theme::dark());
+        assert!(style.contains("font-family: 'Courier New', Courier, monospace;"));
+        assert!(style.contains("font-size: 1.8em;"));
+        assert!(style.contains("text-shadow: 0 0 5px black, 0 0 5px black,"));
+
+        let style = compute_text_style(&Theme::high_contrast());
+        assert!(style.contains("text-shadow: none;"));
+    }
+
+    #[test]
+    fn test_input_style_uses_editing_color() {
+        let theme = Theme {
+            colors: Colors {
+                editing: "lime".into(),
+                ..Colors::default()
+            },
+            ..Theme::minimal()
+        };
+        let style = compute_input_style(&theme);
+        assert!(style.contains("color: lime;"));
+        assert!(style.contains("font-family: sans-serif;"));
+    }
+
+    #[test]
+    fn test_progress_fraction() {
+        assert_eq!(progress_fraction(1500, 1500), 0.0);
+        assert_eq!(progress_fraction(375, 1500), 0.75);
+        assert_eq!(progress_fraction(0, 1500), 1.0);
+        // Overrun stays full
+        assert_eq!(progress_fraction(-60, 1500), 1.0);
+        // More left than was set, e.g. restored from another duration
+        assert_eq!(progress_fraction(2000, 1500), 0.0);
+        assert_eq!(progress_fraction(0, 0), 1.0);
+    }
+
+    #[test]
+    fn test_progress_style_follows_state_color() {
+        let colors = Colors::default();
+        let style = compute_progress_style(Progress::Bar, 0.25, false, true, false, None, &colors);
+        assert_eq!(style, "width: 25.0%; background-color: white; opacity: 1;");
+
+        let style = compute_progress_style(Progress::Ring, 0.5, false, true, true, None, &colors);
+        assert_eq!(
+            style,
+            "stroke: darkturquoise; stroke-dasharray: 50.0 100; opacity: 1;"
+        );
+
+        let style = compute_progress_style(Progress::Fill, 1.0, true, true, false, None, &colors);
+        assert_eq!(
+            style,
+            "width: 100.0%; background-color: red; opacity: 0.25;"
+        );
+
+        assert_eq!(
+            compute_progress_style(Progress::None, 0.5, false, true, false, None, &colors),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_progress_blinks_with_time() {
+        let colors = Colors::default();
+        for progress in [Progress::Ring, Progress::Bar, Progress::Fill] {
+            let style = compute_progress_style(progress, 1.0, true, false, false, None, &colors);
+            assert!(style.ends_with("opacity: 0;"), "{style}");
+        }
+    }
+
+    #[test]
+    fn test_expiry_motion() {
+        let colors = Colors::default();
+        // Only blinking goes dark
+        assert!(!blink_visible(false, ExpiryMotion::Blink));
+        assert!(blink_visible(false, ExpiryMotion::Pulse));
+        assert!(blink_visible(false, ExpiryMotion::Still));
+
+        let pulse = ExpiryCues {
+            motion: ExpiryMotion::Pulse,
+            ..ExpiryCues::default()
+        };
+        let style = compute_timer_style(true, true, false, None, &pulse, &colors);
+        assert!(style.contains("animation: expiry-pulse"));
+        assert!(style.contains("color: red;"));
+        assert!(
+            !compute_timer_style(false, true, false, None, &pulse, &colors).contains("animation")
+        );
+        assert!(
+            !compute_timer_style(true, true, true, None, &pulse, &colors).contains("animation")
+        );
+    }
+
+    #[test]
+    fn test_expiry_invert_and_border() {
+        let colors = Colors::default();
+        let cues = ExpiryCues {
+            invert: true,
+            border: true,
+            ..ExpiryCues::default()
+        };
+        assert_eq!(compute_expiry_style(&cues, false, &colors), "");
+        assert_eq!(
+            compute_expiry_style(&cues, true, &colors),
+            "background-color: red; outline: 0.2em dashed var(--backdrop-color); outline-offset: -0.2em;"
+        );
+        let style = compute_timer_style(true, true, false, None, &cues, &colors);
+        assert!(style.contains("color: var(--backdrop-color);"));
+
+        let border = ExpiryCues {
+            border: true,
+            ..ExpiryCues::default()
+        };
+        assert_eq!(
+            compute_expiry_style(&border, true, &colors),
+            "outline: 0.2em dashed red; outline-offset: -0.2em;"
+        );
+        assert!(compute_container_style(&Theme::dark()).contains("--backdrop-color: #000000;"));
+    }
+
+    #[test]
+    fn test_revealed_near_the_end_and_once_up() {
+        let fade = Fade {
+            idle_opacity: 0.3,
+            reveal_seconds: 60,
+            reveal_scale: 1.5,
+        };
+        assert!(!is_revealed(&fade, 61, false, false));
+        assert!(is_revealed(&fade, 60, false, false));
+        // Paused near the end there is no hurry
+        assert!(!is_revealed(&fade, 30, false, true));
+        assert!(is_revealed(&fade, -5, true, false));
+
+        assert_eq!(
+            compute_fade_style(&fade, false),
+            "--idle-opacity: 0.3; --reveal-scale: 1;"
+        );
+        assert_eq!(
+            compute_fade_style(&fade, true),
+            "--idle-opacity: 1; --reveal-scale: 1.5;"
+        );
+    }
+
+    #[test]
+    fn test_warning_stage_color_and_pulse() {
+        let colors = Colors::default();
+        let stage = WarningStage::default();
+        let style = compute_timer_style(false, true, false, Some(&stage), &cues(), &colors);
+        assert!(style.contains("color: gold;"));
+        assert!(!style.contains("animation"));
+
+        let stage = WarningStage {
+            color: "orange".into(),
+            pulse: true,
+            ..WarningStage::default()
+        };
+        let style = compute_timer_style(false, true, false, Some(&stage), &cues(), &colors);
+        assert!(style.contains("color: orange;"));
+        assert!(style.contains("animation: warning-pulse"));
+
+        // Paused shows as paused, and holds still
+        let style = compute_timer_style(false, true, true, Some(&stage), &cues(), &colors);
+        assert!(style.contains("color: darkturquoise;"));
+        assert!(!style.contains("animation"));
+
+        let style = compute_progress_style(
+            Progress::Bar,
+            0.9,
+            false,
+            true,
+            false,
+            Some(&stage),
+            &colors,
+        );
+        assert!(style.contains("background-color: orange;"));
+    }
+}
@@ -0,0 +1,250 @@
+//! Display themes: the colours, font and backdrop of the timer window.
+//! A few are built in; users may define more in their settings.
+
+/// Colour of the time in each state, as any CSS colour
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct Colors {
+    pub running: String,
+    pub paused: String,
+    /// Close to time up
+    pub warning: String,
+    /// Once time is up, while blinking
+    pub expired: String,
+    /// The time-entry field
+    pub editing: String,
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Theme::dark().colors
+    }
+}
+
+/// Everything about how the timer looks. Fields a user theme leaves out
+/// are taken from `dark`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct Theme {
+    pub colors: Colors,
+    /// CSS `font-family` list
+    pub font_family: String,
+    /// Size of the time, in `em`
+    pub font_size_em: f32,
+    /// Backdrop colour, `#rrggbb`
+    pub background: String,
+    /// Backdrop opacity, from 0 (clear) to 1
+    pub background_opacity: f32,
+    pub corner_radius_px: u32,
+    /// Colour of the glow that keeps the time readable on any backdrop,
+    /// empty for none
+    pub text_shadow: String,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+/// Names of the built-in themes, in the order they are offered
+pub const BUILTIN_THEMES: [&str; 4] = ["dark", "light", "high-contrast", "minimal"];
+
+const MONOSPACE: &str = "'Courier New', Courier, monospace";
+
+impl Theme {
+    /// Light digits on a faint dark backdrop, the original look
+    pub fn dark() -> Self {
+        Self {
+            colors: Colors {
+                running: "white".into(),
+                paused: "darkturquoise".into(),
+                warning: "gold".into(),
+                expired: "red".into(),
+                editing: "white".into(),
+            },
+            font_family: MONOSPACE.into(),
+            font_size_em: 1.8,
+            background: "#000000".into(),
+            background_opacity: 0.2,
+            corner_radius_px: 8,
+            text_shadow: "black".into(),
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            colors: Colors {
+                running: "#222222".into(),
+                paused: "teal".into(),
+                warning: "darkorange".into(),
+                expired: "crimson".into(),
+                editing: "#222222".into(),
+            },
+            background: "#ffffff".into(),
+            background_opacity: 0.6,
+            text_shadow: "white".into(),
+            ..Self::dark()
+        }
+    }
+
+    /// Pure colours on a solid black backdrop
+    pub fn high_contrast() -> Self {
+        Self {
+            colors: Colors {
+                running: "#ffffff".into(),
+                paused: "#00ffff".into(),
+                warning: "#ffff00".into(),
+                expired: "#ff0000".into(),
+                editing: "#ffffff".into(),
+            },
+            background_opacity: 1.0,
+            corner_radius_px: 0,
+            text_shadow: String::new(),
+            ..Self::dark()
+        }
+    }
+
+    /// Bare digits, no backdrop
+    pub fn minimal() -> Self {
+        Self {
+            font_family: "sans-serif".into(),
+            font_size_em: 1.6,
+            background_opacity: 0.0,
+            corner_radius_px: 0,
+            ..Self::dark()
+        }
+    }
+
+    /// A built-in theme by name
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "minimal" => Some(Self::minimal()),
+            _ => None,
+        }
+    }
+
+    /// Every problem with the theme, each prefixed with `name`
+    pub fn problems(&self, name: &str) -> Vec<String> {
+        let mut problems = Vec::new();
+        let colors = [
+            ("colors.running", &self.colors.running),
+            ("colors.paused", &self.colors.paused),
+            ("colors.warning", &self.colors.warning),
+            ("colors.expired", &self.colors.expired),
+            ("colors.editing", &self.colors.editing),
+        ];
+        for (field, color) in colors {
+            if !is_css_value(color) {
+                problems.push(format!(
+                    "`{name}.{field}` must be a CSS colour, not {color:?}"
+                ));
+            }
+        }
+        if !is_css_value(&self.font_family) {
+            problems.push(format!(
+                "`{name}.font_family` must be a CSS font list, not {:?}",
+                self.font_family
+            ));
+        }
+        if !(0.5..=10.0).contains(&self.font_size_em) {
+            problems.push(format!(
+                "`{name}.font_size_em` must be between 0.5 and 10, not {}",
+                self.font_size_em
+            ));
+        }
+        if parse_hex_color(&self.background).is_none() {
+            problems.push(format!(
+                "`{name}.background` must be a colour like \"#1e1e1e\", not {:?}",
+                self.background
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.background_opacity) {
+            problems.push(format!(
+                "`{name}.background_opacity` must be between 0 and 1, not {}",
+                self.background_opacity
+            ));
+        }
+        if self.corner_radius_px > 50 {
+            problems.push(format!(
+                "`{name}.corner_radius_px` must be between 0 and 50, not {}",
+                self.corner_radius_px
+            ));
+        }
+        if !self.text_shadow.is_empty() && !is_css_value(&self.text_shadow) {
+            problems.push(format!(
+                "`{name}.text_shadow` must be a CSS colour or empty, not {:?}",
+                self.text_shadow
+            ));
+        }
+        problems
+    }
+}
+
+/// Safe to interpolate into an inline style as a single value
+pub(crate) fn is_css_value(value: &str) -> bool {
+    !value.trim().is_empty() && !value.contains([';', '{', '}', '"', '<', '>'])
+}
+
+/// `#rrggbb` as its components
+pub fn parse_hex_color(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let component = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((component(0)?, component(2)?, component(4)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_themes_are_valid() {
+        for name in BUILTIN_THEMES {
+            let theme = Theme::builtin(name).unwrap();
+            assert_eq!(theme.problems(name), Vec::<String>::new(), "{name}");
+        }
+        assert_eq!(Theme::builtin("solarized"), None);
+    }
+
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(parse_hex_color("#1e1E1e"), Some((30, 30, 30)));
+        assert_eq!(parse_hex_color("1e1e1e"), None);
+        assert_eq!(parse_hex_color("#fff"), None);
+        assert_eq!(parse_hex_color("#gggggg"), None);
+    }
+
+    #[test]
+    fn test_problems_name_the_theme() {
+        let theme = Theme {
+            font_size_em: 0.0,
+            background: "black".into(),
+            background_opacity: 1.5,
+            text_shadow: "0 0 5px black; color: red".into(),
+            ..Theme::dark()
+        };
+        assert_eq!(
+            theme.problems("themes.mine"),
+            [
+                "`themes.mine.font_size_em` must be between 0.5 and 10, not 0",
+                "`themes.mine.background` must be a colour like \"#1e1e1e\", not \"black\"",
+                "`themes.mine.background_opacity` must be between 0 and 1, not 1.5",
+                "`themes.mine.text_shadow` must be a CSS colour or empty, not \"0 0 5px black; color: red\"",
+            ]
+        );
+    }
+}
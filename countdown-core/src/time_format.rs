@@ -1,4 +1,4 @@
-/// Utility functions for the countdown app
+//! Utility functions for the countdown app
 
 /// Format total seconds into "MM:SS" string, supporting negative values
 pub fn format_time(total_secs: i32) -> String {
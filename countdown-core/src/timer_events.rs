@@ -0,0 +1,187 @@
+//! What happens to a `TimerEngine`, as it happens. Subscribers to
+//! `TimerEngine::subscribe` hear each event once, in the order they
+//! happened, after the whole command or countdown step that caused them,
+//! so the snapshot they read is already up to date.
+
+/// A moment in the timer's life
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "event", rename_all = "snake_case")
+)]
+pub enum TimerEvent {
+    /// Counting down from `seconds`: the first window attached, a new
+    /// duration was set or restarted, or time up was snoozed
+    Started {
+        seconds: i32,
+    },
+    Paused {
+        remaining: i32,
+    },
+    Resumed {
+        remaining: i32,
+    },
+    /// One countdown step
+    Tick {
+        remaining: i32,
+    },
+    /// Counted down into the warning stage from `at_seconds` left
+    WarningReached {
+        at_seconds: i32,
+    },
+    /// Time is up: the countdown passed zero and blinking began
+    Expired,
+    /// Another whole minute past zero
+    OverrunMinute {
+        minutes: i32,
+    },
+    /// The overrun limit was reached, where the countdown stays
+    Stopped {
+        remaining: i32,
+    },
+    /// Put back to `remaining` by a reset, or to an earlier state by undo
+    /// or redo; the snapshot tells whether it is running
+    Reset {
+        remaining: i32,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Settings;
+    use crate::timer_provider::tests::FakeProvider;
+    use crate::timer_service::{TimerCommand, TimerEngine};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Engine on a `FakeProvider`, which runs each new interval once at
+    /// once, with every event it sends
+    fn engine(settings: Settings) -> (TimerEngine, Rc<RefCell<Vec<TimerEvent>>>) {
+        let engine = TimerEngine::new(Rc::new(FakeProvider::new()), settings);
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let heard = events.clone();
+        engine.subscribe(move |event| heard.borrow_mut().push(event.clone()));
+        (engine, events)
+    }
+
+    fn taken(events: &RefCell<Vec<TimerEvent>>) -> Vec<TimerEvent> {
+        events.take()
+    }
+
+    #[test]
+    fn test_lifecycle_in_order() {
+        let (engine, events) = engine(Settings {
+            default_seconds: 61,
+            ..Settings::default()
+        });
+        engine.apply(TimerCommand::Attach {
+            saved_seconds: None,
+        });
+        assert_eq!(
+            taken(&events),
+            [
+                TimerEvent::Started { seconds: 61 },
+                TimerEvent::Tick { remaining: 60 },
+                TimerEvent::WarningReached { at_seconds: 60 },
+            ]
+        );
+
+        engine.apply(TimerCommand::Pause);
+        // Guards stop these, so nothing happens
+        engine.apply(TimerCommand::Pause);
+        engine.apply(TimerCommand::Reset);
+        engine.apply(TimerCommand::Resume);
+        assert_eq!(
+            taken(&events),
+            [
+                TimerEvent::Paused { remaining: 60 },
+                TimerEvent::Resumed { remaining: 60 },
+                TimerEvent::Tick { remaining: 59 },
+            ]
+        );
+
+        engine.apply(TimerCommand::SetDuration { seconds: 0 });
+        assert_eq!(
+            taken(&events),
+            [
+                TimerEvent::Started { seconds: 0 },
+                TimerEvent::Tick { remaining: -1 },
+                TimerEvent::Expired,
+            ]
+        );
+
+        engine.apply(TimerCommand::Reset);
+        // Back to before the new duration
+        engine.apply(TimerCommand::Undo);
+        assert_eq!(
+            taken(&events),
+            [
+                TimerEvent::Reset { remaining: 0 },
+                TimerEvent::Tick { remaining: -1 },
+                TimerEvent::Expired,
+                TimerEvent::Reset { remaining: 59 },
+                TimerEvent::Tick { remaining: 58 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overrun_minutes_and_stop() {
+        let (engine, events) = engine(Settings {
+            overrun_limit_seconds: 61,
+            ..Settings::default()
+        });
+        engine.apply(TimerCommand::Attach {
+            saved_seconds: Some(-59),
+        });
+        assert_eq!(
+            taken(&events),
+            [
+                TimerEvent::Started { seconds: -59 },
+                TimerEvent::Tick { remaining: -60 },
+                TimerEvent::Expired,
+                TimerEvent::OverrunMinute { minutes: 1 },
+            ]
+        );
+
+        engine.apply(TimerCommand::SetDuration { seconds: -61 });
+        assert_eq!(
+            taken(&events),
+            [
+                TimerEvent::Started { seconds: -61 },
+                TimerEvent::Expired,
+                TimerEvent::Stopped { remaining: -61 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_events_from_subscribers_follow() {
+        let (engine, events) = engine(Settings::default());
+        let engine = Rc::new(engine);
+        let snoozer = Rc::downgrade(&engine);
+        // Snoozes as soon as time is up, from inside the event
+        engine.subscribe(move |event| {
+            if let (TimerEvent::Expired, Some(engine)) = (event, snoozer.upgrade()) {
+                assert!(engine.snapshot().blinking);
+                engine.apply(TimerCommand::Snooze { seconds: Some(5) });
+            }
+        });
+        engine.apply(TimerCommand::Attach {
+            saved_seconds: Some(0),
+        });
+        assert_eq!(
+            taken(&events),
+            [
+                TimerEvent::Started { seconds: 0 },
+                TimerEvent::Tick { remaining: -1 },
+                TimerEvent::Expired,
+                TimerEvent::Started { seconds: 5 },
+                TimerEvent::Tick { remaining: 4 },
+            ]
+        );
+        assert_eq!(engine.snapshot().remaining, 4);
+    }
+}
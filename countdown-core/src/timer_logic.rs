@@ -0,0 +1,256 @@
+//! Pure logic for countdown updates, blink toggling and warning stages
+
+use crate::settings::{EscalationStage, WarningStage};
+
+/// Result of a countdown update step
+pub struct CountdownUpdate {
+    pub remaining: i32,
+    pub should_blink: bool,
+
+    /// Whether to stop the countdown at the overrun limit
+    pub should_stop: bool,
+}
+
+/// Update countdown: decrease remaining, signal blink start when zero, stop
+/// at `minimum` (MINIMUM_SECONDS unless the settings say otherwise)
+pub fn update_countdown(remaining: i32, minimum: i32) -> CountdownUpdate {
+    if remaining > 0 {
+        CountdownUpdate {
+            remaining: remaining - 1,
+            should_blink: false,
+            should_stop: false,
+        }
+    } else if remaining > minimum {
+        CountdownUpdate {
+            remaining: remaining - 1, // From 0, starts the negative countdown
+            should_blink: true,
+            should_stop: false,
+        }
+    } else {
+        // Stop at the limit
+        CountdownUpdate {
+            remaining: minimum,
+            should_blink: true,
+            should_stop: true,
+        }
+    }
+}
+
+/// Toggle blink visibility
+pub fn toggle_blink(visible: bool) -> bool {
+    !visible
+}
+
+/// The most urgent warning stage reached with `remaining` seconds left, if
+/// any. None once time is up, when blinking takes over.
+pub fn warning_stage(stages: &[WarningStage], remaining: i32) -> Option<&WarningStage> {
+    if remaining <= 0 {
+        return None;
+    }
+    stages
+        .iter()
+        .filter(|stage| remaining <= stage.at_seconds)
+        .min_by_key(|stage| stage.at_seconds)
+}
+
+/// The stage reached by counting down from `previous` to `remaining`, if a
+/// more urgent one than before. Setting a shorter time, or pausing and
+/// resuming, reaches none.
+pub fn entered_warning_stage(
+    stages: &[WarningStage],
+    previous: i32,
+    remaining: i32,
+) -> Option<&WarningStage> {
+    if !counted_down(previous, remaining) {
+        return None;
+    }
+    let stage = warning_stage(stages, remaining)?;
+    match warning_stage(stages, previous) {
+        Some(before) if before.at_seconds <= stage.at_seconds => None,
+        _ => Some(stage),
+    }
+}
+
+/// The latest escalation stage reached with `remaining` seconds left, if
+/// time has been up long enough for any
+pub fn escalation_stage(stages: &[EscalationStage], remaining: i32) -> Option<&EscalationStage> {
+    stages
+        .iter()
+        .filter(|stage| -remaining >= stage.after_seconds)
+        .max_by_key(|stage| stage.after_seconds)
+}
+
+/// The stage reached by counting from `previous` to `remaining`, if a later
+/// one than before
+pub fn entered_escalation_stage(
+    stages: &[EscalationStage],
+    previous: i32,
+    remaining: i32,
+) -> Option<&EscalationStage> {
+    if !counted_down(previous, remaining) {
+        return None;
+    }
+    let stage = escalation_stage(stages, remaining)?;
+    match escalation_stage(stages, previous) {
+        Some(before) if before.after_seconds >= stage.after_seconds => None,
+        _ => Some(stage),
+    }
+}
+
+/// Whether `remaining` is one countdown step on from `previous`, rather than
+/// a pause, a reset or a new duration
+fn counted_down(previous: i32, remaining: i32) -> bool {
+    remaining == previous - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        entered_escalation_stage, entered_warning_stage, escalation_stage, toggle_blink,
+        update_countdown, warning_stage,
+    };
+    use crate::config::MINIMUM_SECONDS;
+    use crate::settings::{EscalationStage, WarningStage};
+
+    #[test]
+    fn test_update_countdown_decrements() {
+        let result = update_countdown(10, MINIMUM_SECONDS);
+        assert_eq!(result.remaining, 9);
+        assert!(!result.should_blink);
+        assert!(!result.should_stop);
+    }
+
+    #[test]
+    fn test_update_countdown_blink_signal() {
+        let result = update_countdown(0, MINIMUM_SECONDS);
+        assert_eq!(result.remaining, -1);
+        assert!(result.should_blink);
+        assert!(!result.should_stop);
+    }
+
+    #[test]
+    fn test_update_countdown_negative() {
+        let result = update_countdown(-10, MINIMUM_SECONDS);
+        assert_eq!(result.remaining, -11);
+        assert!(result.should_blink);
+        assert!(!result.should_stop);
+    }
+
+    #[test]
+    fn test_update_countdown_stop_at_limit() {
+        let result = update_countdown(-3599, MINIMUM_SECONDS);
+        assert_eq!(result.remaining, -3599);
+        assert!(result.should_blink);
+        assert!(result.should_stop);
+    }
+
+    #[test]
+    fn test_update_countdown_custom_limit() {
+        let result = update_countdown(-59, -60);
+        assert_eq!(result.remaining, -60);
+        assert!(!result.should_stop);
+
+        let result = update_countdown(-60, -60);
+        assert_eq!(result.remaining, -60);
+        assert!(result.should_stop);
+    }
+
+    #[test]
+    fn test_update_countdown_zero_limit_stops_at_zero() {
+        let result = update_countdown(0, 0);
+        assert_eq!(result.remaining, 0);
+        assert!(result.should_blink);
+        assert!(result.should_stop);
+    }
+
+    #[test]
+    fn test_update_countdown_clamps_to_shortened_limit() {
+        // The overrun limit was shortened below the current overrun
+        let result = update_countdown(-100, -50);
+        assert_eq!(result.remaining, -50);
+        assert!(result.should_stop);
+    }
+
+    #[test]
+    fn test_toggle_blink() {
+        assert!(toggle_blink(false));
+        assert!(!toggle_blink(true));
+    }
+
+    fn at(stage: Option<&WarningStage>) -> Option<i32> {
+        stage.map(|stage| stage.at_seconds)
+    }
+
+    #[test]
+    fn test_warning_stage_thresholds() {
+        let stages = WarningStage::defaults();
+        assert_eq!(at(warning_stage(&stages, 301)), None);
+        assert_eq!(at(warning_stage(&stages, 300)), Some(300));
+        assert_eq!(at(warning_stage(&stages, 61)), Some(300));
+        assert_eq!(at(warning_stage(&stages, 60)), Some(60));
+        assert_eq!(at(warning_stage(&stages, 1)), Some(60));
+        // Time up is blinking, not a warning
+        assert_eq!(at(warning_stage(&stages, 0)), None);
+        assert_eq!(at(warning_stage(&stages, -30)), None);
+        assert_eq!(at(warning_stage(&[], 10)), None);
+    }
+
+    #[test]
+    fn test_entered_warning_stage_on_countdown() {
+        let stages = WarningStage::defaults();
+        assert_eq!(at(entered_warning_stage(&stages, 302, 301)), None);
+        assert_eq!(at(entered_warning_stage(&stages, 301, 300)), Some(300));
+        assert_eq!(at(entered_warning_stage(&stages, 300, 299)), None);
+        assert_eq!(at(entered_warning_stage(&stages, 61, 60)), Some(60));
+        assert_eq!(at(entered_warning_stage(&stages, 1, 0)), None);
+    }
+
+    #[test]
+    fn test_pause_and_resume_across_threshold() {
+        let stages = WarningStage::defaults();
+        // Paused at 5:01 and resumed: the next step still enters the stage
+        assert_eq!(at(entered_warning_stage(&stages, 301, 301)), None);
+        assert_eq!(at(entered_warning_stage(&stages, 301, 300)), Some(300));
+        // Paused within the stage: it stays, and resuming enters nothing new
+        assert_eq!(at(warning_stage(&stages, 250)), Some(300));
+        assert_eq!(at(entered_warning_stage(&stages, 250, 250)), None);
+        assert_eq!(at(entered_warning_stage(&stages, 250, 249)), None);
+    }
+
+    #[test]
+    fn test_setting_shorter_time_enters_no_stage() {
+        let stages = WarningStage::defaults();
+        assert_eq!(at(entered_warning_stage(&stages, 1500, 45)), None);
+        // A reset leaves the stages, so they are entered again next time
+        assert_eq!(at(warning_stage(&stages, 1500)), None);
+        assert_eq!(at(entered_warning_stage(&stages, 301, 300)), Some(300));
+    }
+
+    #[test]
+    fn test_escalation_stage_by_overrun() {
+        let stages = EscalationStage::defaults();
+        let after = |remaining| escalation_stage(&stages, remaining).map(|s| s.after_seconds);
+        assert_eq!(after(10), None);
+        assert_eq!(after(-299), None);
+        assert_eq!(after(-300), Some(300));
+        assert_eq!(after(-899), Some(300));
+        assert_eq!(after(-900), Some(900));
+        assert_eq!(after(-3599), Some(900));
+    }
+
+    #[test]
+    fn test_entered_escalation_stage_once() {
+        let stages = EscalationStage::defaults();
+        let entered = |previous, remaining| {
+            entered_escalation_stage(&stages, previous, remaining).map(|s| s.after_seconds)
+        };
+        assert_eq!(entered(-299, -300), Some(300));
+        assert_eq!(entered(-300, -301), None);
+        assert_eq!(entered(-899, -900), Some(900));
+        // Stopped at the limit: no steps, nothing entered again
+        assert_eq!(entered(-3599, -3599), None);
+        // A reset starts over
+        assert_eq!(entered(-950, 1500), None);
+        assert_eq!(entered(-299, -300), Some(300));
+    }
+}
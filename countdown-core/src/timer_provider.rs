@@ -0,0 +1,228 @@
+use crate::interval_queue::IntervalQueue;
+use std::cell::Cell;
+
+/// Cancel handle returned by TimerProvider
+pub trait TimerHandle {
+    fn cancel(&mut self);
+}
+
+/// Abstraction over timer creation (interval scheduling)
+pub trait TimerProvider {
+    fn set_interval(&self, ms: u32, callback: Box<dyn FnMut()>) -> Box<dyn TimerHandle>;
+}
+
+/// Provider on a clock that moves only when `advance` says, for testing
+/// anything driven by a `TimerProvider` over many steps. Intervals are due
+/// as `IntervalQueue` has them.
+#[derive(Default)]
+pub struct ManualClockProvider {
+    now_ms: Cell<u64>,
+    intervals: IntervalQueue<u64, u64>,
+}
+
+impl ManualClockProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Milliseconds advanced so far; inside a callback, when it was due
+    pub fn now_ms(&self) -> u64 {
+        self.now_ms.get()
+    }
+
+    /// When the earliest live interval is next due, or `None` when none is
+    /// set
+    pub fn next_due_ms(&self) -> Option<u64> {
+        self.intervals.next_due()
+    }
+
+    /// Move the clock on by `ms`, as a machine awake the whole time would
+    /// see it: every callback that falls due on the way runs, each as many
+    /// times as it falls due. Callbacks may set or cancel intervals,
+    /// including their own; a new one counts from the moment it was set.
+    pub fn advance(&self, ms: u64) {
+        let until = self.now_ms.get() + ms;
+        while let Some(due) = self.next_due_ms().filter(|&due| due <= until) {
+            self.intervals.run_due(due, |due| self.now_ms.set(due));
+        }
+        self.now_ms.set(until);
+    }
+
+    /// Move the clock on by `ms` at once, as a machine waking from sleep
+    /// finds it: each interval that fell due on the way runs once, however
+    /// many periods it missed
+    pub fn wake_after(&self, ms: u64) {
+        let until = self.now_ms.get() + ms;
+        self.now_ms.set(until);
+        self.intervals.run_due(until, |_| ());
+    }
+}
+
+impl TimerProvider for ManualClockProvider {
+    /// A period of 0 is taken as 1 ms, as a browser would at least
+    fn set_interval(&self, ms: u32, callback: Box<dyn FnMut()>) -> Box<dyn TimerHandle> {
+        self.intervals
+            .set_interval(self.now_ms.get(), u64::from(ms.max(1)), callback)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Fake handle to track cancellation
+    struct FakeHandle {
+        pub cancelled: bool,
+    }
+
+    impl TimerHandle for FakeHandle {
+        fn cancel(&mut self) {
+            self.cancelled = true;
+        }
+    }
+
+    /// Fake provider that records calls and invokes callback immediately
+    #[derive(Default)]
+    pub struct FakeProvider {
+        pub calls: Rc<RefCell<Vec<u32>>>,
+    }
+
+    impl FakeProvider {
+        pub fn new() -> Self {
+            Self {
+                calls: Rc::new(RefCell::new(Vec::new())),
+            }
+        }
+    }
+
+    impl TimerProvider for FakeProvider {
+        fn set_interval(&self, ms: u32, mut callback: Box<dyn FnMut()>) -> Box<dyn TimerHandle> {
+            self.calls.borrow_mut().push(ms);
+            // simulate immediate trigger
+            (callback)();
+            Box::new(FakeHandle { cancelled: false })
+        }
+    }
+
+    #[test]
+    fn test_fake_provider() {
+        let provider = FakeProvider::new();
+        let calls_ref = provider.calls.clone();
+        // use `Box<dyn FnMut()>` to match signature, with `move` so closure is 'static
+        let handle = provider.set_interval(
+            123,
+            Box::new(move || {
+                calls_ref.borrow_mut().push(999);
+            }),
+        );
+        assert_eq!(&*provider.calls.borrow(), &[123, 999]);
+        // test cancellation
+        let mut h = handle;
+        h.cancel();
+        // no panic means cancel worked
+    }
+
+    /// Intervals on `clock` that note their name in `fired` each time
+    fn noting(
+        clock: &ManualClockProvider,
+        fired: &Rc<RefCell<Vec<&'static str>>>,
+        intervals: &[(&'static str, u32)],
+    ) -> Vec<Box<dyn TimerHandle>> {
+        intervals
+            .iter()
+            .map(|&(name, ms)| {
+                let fired = fired.clone();
+                clock.set_interval(ms, Box::new(move || fired.borrow_mut().push(name)))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_manual_clock_interleaves_in_order() {
+        let clock = ManualClockProvider::new();
+        let fired = Rc::new(RefCell::new(Vec::new()));
+        let _handles = noting(&clock, &fired, &[("second", 1000), ("blink", 500)]);
+
+        clock.advance(499);
+        assert!(fired.borrow().is_empty());
+        assert_eq!(clock.next_due_ms(), Some(500));
+
+        // On a tie the interval set first runs first
+        clock.advance(1501);
+        assert_eq!(
+            &*fired.borrow(),
+            &["blink", "second", "blink", "blink", "second", "blink"]
+        );
+        assert_eq!(clock.now_ms(), 2000);
+    }
+
+    #[test]
+    fn test_manual_clock_wakes_without_a_burst() {
+        let clock = ManualClockProvider::new();
+        let fired = Rc::new(RefCell::new(Vec::new()));
+        let _handles = noting(&clock, &fired, &[("second", 1000), ("blink", 500)]);
+
+        // An hour asleep
+        clock.wake_after(3_600_000);
+        assert_eq!(&*fired.borrow(), &["blink", "second"]);
+        assert_eq!(clock.next_due_ms(), Some(3_600_500));
+
+        clock.advance(1000);
+        assert_eq!(
+            &*fired.borrow(),
+            &["blink", "second", "blink", "second", "blink"]
+        );
+    }
+
+    #[test]
+    fn test_manual_clock_cancel() {
+        let clock = ManualClockProvider::new();
+        let fired = Rc::new(RefCell::new(Vec::new()));
+        let mut handles = noting(&clock, &fired, &[("fast", 100), ("slow", 300)]);
+
+        clock.advance(250);
+        handles[0].cancel();
+        clock.advance(1000);
+        assert_eq!(
+            &*fired.borrow(),
+            &["fast", "fast", "slow", "slow", "slow", "slow"]
+        );
+
+        handles[1].cancel();
+        assert_eq!(clock.next_due_ms(), None);
+    }
+
+    #[test]
+    fn test_manual_clock_callbacks_set_and_cancel() {
+        let clock = Rc::new(ManualClockProvider::new());
+        let fired = Rc::new(RefCell::new(Vec::new()));
+        let own: Rc<RefCell<Option<Box<dyn TimerHandle>>>> = Rc::default();
+
+        // Runs twice, cancelling itself and setting a slower one the second
+        // time, 200 ms in
+        let (inner_clock, inner_fired, inner_own) = (clock.clone(), fired.clone(), own.clone());
+        let mut runs = 0;
+        *own.borrow_mut() = Some(clock.set_interval(
+            100,
+            Box::new(move || {
+                runs += 1;
+                inner_fired.borrow_mut().push(inner_clock.now_ms());
+                if runs == 2 {
+                    if let Some(mut handle) = inner_own.borrow_mut().take() {
+                        handle.cancel();
+                    }
+                    let fired = inner_fired.clone();
+                    let at = inner_clock.clone();
+                    // Dropping a handle leaves its interval running
+                    inner_clock
+                        .set_interval(250, Box::new(move || fired.borrow_mut().push(at.now_ms())));
+                }
+            }),
+        ));
+
+        clock.advance(1000);
+        assert_eq!(&*fired.borrow(), &[100, 200, 450, 700, 950]);
+    }
+}
@@ -0,0 +1,1038 @@
+use crate::event_logic::handle_click;
+use crate::history::History;
+use crate::settings::Settings;
+use crate::timer_events::TimerEvent;
+use crate::timer_logic::{entered_warning_stage, toggle_blink, update_countdown};
+use crate::timer_provider::{TimerHandle, TimerProvider};
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+/// Everything a window needs to draw the timer
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimerSnapshot {
+    pub remaining: i32,
+    /// Duration a reset returns to: the most recently set one
+    pub reset_time: i32,
+    /// Whether `reset_time` was set, by `SetDuration` or a saved duration,
+    /// rather than taken from the default
+    pub duration_set: bool,
+    pub paused: bool,
+    pub blinking: bool,
+    /// Blink phase; only meaningful while blinking
+    pub blink_visible: bool,
+    /// Time-entry mode, which pauses the countdown while it is open
+    pub editing: bool,
+    /// Whether `Undo` has a state to go back to
+    pub can_undo: bool,
+    /// Whether `Redo` has a state to return to
+    pub can_redo: bool,
+}
+
+impl TimerSnapshot {
+    /// A stopped timer showing `initial_seconds`
+    pub fn new(initial_seconds: i32) -> Self {
+        Self {
+            remaining: initial_seconds,
+            reset_time: initial_seconds,
+            duration_set: false,
+            paused: false,
+            blinking: false,
+            blink_visible: true,
+            editing: false,
+            can_undo: false,
+            can_redo: false,
+        }
+    }
+}
+
+/// Every transition the timer supports. Each applies the same rules the
+/// matching key or click always has, guards included.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "command", rename_all = "snake_case")
+)]
+pub enum TimerCommand {
+    /// Start counting from the remembered duration, unless already started.
+    /// The first window to load sends this; later ones only pick up state.
+    Attach { saved_seconds: Option<i32> },
+    /// Restart from the set duration
+    Start,
+    /// 'p' while running; ignored while blinking
+    Pause,
+    /// 'p' while paused
+    Resume,
+    /// 'p'
+    TogglePause,
+    /// Click on the time; ignored while paused
+    Reset,
+    /// Enter in time-entry mode
+    SetDuration { seconds: i32 },
+    /// Once time is up, count down `seconds` more, or the settings'
+    /// `snooze_seconds`, keeping the set duration; ignored before then
+    Snooze { seconds: Option<i32> },
+    /// 'f'; pauses a running countdown
+    EnterEdit,
+    /// Escape in time-entry mode; resumes the countdown
+    CancelEdit,
+    /// Ctrl+Z: back to the state before the last reset, restart or new
+    /// duration; ignored in time-entry mode
+    Undo,
+    /// Ctrl+Shift+Z: forward again to the state undone from
+    Redo,
+}
+
+type Listener = Box<dyn FnMut(&TimerEvent)>;
+
+/// The countdown and blink intervals, driven by a `TimerProvider`
+pub struct TimerEngine {
+    inner: Rc<EngineInner>,
+}
+
+struct EngineInner {
+    provider: Rc<dyn TimerProvider>,
+    settings: RefCell<Settings>,
+    state: RefCell<TimerSnapshot>,
+    started: Cell<bool>,
+    countdown_timer_handle: RefCell<Option<Box<dyn TimerHandle>>>,
+    blink_timer_handle: RefCell<Option<Box<dyn TimerHandle>>>,
+    /// Period of the running blink interval, which escalation may shorten
+    blink_interval_ms: Cell<u32>,
+    history: RefCell<History>,
+    listeners: RefCell<Vec<Listener>>,
+    /// Events not yet heard, oldest first
+    pending: RefCell<VecDeque<TimerEvent>>,
+    /// Commands and countdown steps under way; events wait for them all
+    busy: Cell<u32>,
+    delivering: Cell<bool>,
+}
+
+impl TimerEngine {
+    /// A stopped timer showing the default duration; `Attach` starts it
+    pub fn new(provider: Rc<dyn TimerProvider>, settings: Settings) -> Self {
+        Self {
+            inner: Rc::new(EngineInner {
+                provider,
+                state: RefCell::new(TimerSnapshot::new(settings.default_seconds)),
+                settings: RefCell::new(settings),
+                started: Cell::new(false),
+                countdown_timer_handle: RefCell::new(None),
+                blink_timer_handle: RefCell::new(None),
+                blink_interval_ms: Cell::new(0),
+                history: RefCell::new(History::default()),
+                listeners: RefCell::new(Vec::new()),
+                pending: RefCell::new(VecDeque::new()),
+                busy: Cell::new(0),
+                delivering: Cell::new(false),
+            }),
+        }
+    }
+
+    pub fn snapshot(&self) -> TimerSnapshot {
+        self.inner.state.borrow().clone()
+    }
+
+    /// Hear every `TimerEvent` from now on. A listener may send commands;
+    /// the events they cause are heard after the current one.
+    pub fn subscribe(&self, listener: impl FnMut(&TimerEvent) + 'static) {
+        self.inner.listeners.borrow_mut().push(Box::new(listener));
+    }
+
+    /// Switch to new settings without disturbing the countdown. A changed
+    /// default becomes the duration a reset returns to, unless one was set
+    /// with `SetDuration`; running intervals restart at their new period.
+    pub fn apply_settings(&self, settings: Settings) {
+        self.inner.batch(|| self.apply_settings_now(settings));
+    }
+
+    fn apply_settings_now(&self, settings: Settings) {
+        let inner = &self.inner;
+        let previous = inner.settings.replace(settings.clone());
+        let state = inner.snapshot();
+
+        if settings.default_seconds != previous.default_seconds && !state.duration_set {
+            inner.update(|s| s.reset_time = settings.default_seconds);
+        }
+
+        let running = inner.started.get() && !state.paused;
+        let stopped_at_limit = running && state.remaining <= previous.minimum_seconds();
+        if (stopped_at_limit && state.remaining > settings.minimum_seconds())
+            || (running
+                && !stopped_at_limit
+                && settings.countdown_interval_ms != previous.countdown_interval_ms)
+        {
+            inner.start_countdown_timer();
+        }
+
+        inner.refresh_blink_interval();
+    }
+
+    pub fn apply(&self, command: TimerCommand) {
+        self.inner.batch(|| self.apply_now(command));
+    }
+
+    fn apply_now(&self, command: TimerCommand) {
+        let inner = &self.inner;
+        let state = inner.snapshot();
+        let undoable = matches!(
+            command,
+            TimerCommand::Start
+                | TimerCommand::Reset
+                | TimerCommand::SetDuration { .. }
+                | TimerCommand::Snooze { .. }
+        );
+        match command {
+            TimerCommand::Attach { saved_seconds } => {
+                if inner.started.get() {
+                    return;
+                }
+                if let Some(seconds) = saved_seconds {
+                    inner.update(|s| {
+                        s.remaining = seconds;
+                        s.reset_time = seconds;
+                        s.duration_set = true;
+                    });
+                }
+                inner.emit(TimerEvent::Started {
+                    seconds: inner.snapshot().remaining,
+                });
+                inner.start_countdown_timer();
+            }
+            TimerCommand::Start => inner.set_duration(state.reset_time),
+            TimerCommand::Pause => inner.pause(),
+            TimerCommand::Resume => inner.resume(),
+            TimerCommand::TogglePause => {
+                if state.paused {
+                    inner.resume();
+                } else {
+                    inner.pause();
+                }
+            }
+            TimerCommand::Reset => {
+                if state.paused {
+                    return;
+                }
+                let result = handle_click(state.remaining, state.blinking, Some(state.reset_time));
+                if result.should_cancel_blink {
+                    cancel(&inner.blink_timer_handle);
+                }
+                inner.update(|s| {
+                    s.remaining = result.reset_remaining;
+                    s.blinking = result.is_blinking;
+                    s.blink_visible = result.is_visible;
+                });
+                inner.emit(TimerEvent::Reset {
+                    remaining: result.reset_remaining,
+                });
+                inner.start_countdown_timer();
+            }
+            TimerCommand::SetDuration { seconds } => {
+                inner.update(|s| s.duration_set = true);
+                inner.set_duration(seconds);
+            }
+            TimerCommand::Snooze { seconds } => {
+                if !state.blinking || state.editing {
+                    return;
+                }
+                let seconds = seconds.unwrap_or(inner.settings.borrow().snooze_seconds);
+                cancel(&inner.blink_timer_handle);
+                inner.update(|s| {
+                    s.remaining = seconds;
+                    s.blinking = false;
+                    s.blink_visible = true;
+                    s.paused = false;
+                });
+                inner.emit(TimerEvent::Started { seconds });
+                inner.start_countdown_timer();
+            }
+            TimerCommand::EnterEdit => {
+                if state.editing {
+                    return;
+                }
+                inner.update(|s| s.editing = true);
+                // Pause the timer when entering input mode
+                if !state.paused && !state.blinking {
+                    inner.update(|s| s.paused = true);
+                    cancel(&inner.countdown_timer_handle);
+                    inner.emit(TimerEvent::Paused {
+                        remaining: state.remaining,
+                    });
+                }
+            }
+            TimerCommand::CancelEdit => {
+                if !state.editing {
+                    return;
+                }
+                inner.update(|s| s.editing = false);
+                // Resume timer if it was paused when entering input mode
+                if state.paused && !state.blinking {
+                    inner.update(|s| s.paused = false);
+                    inner.emit(TimerEvent::Resumed {
+                        remaining: state.remaining,
+                    });
+                    inner.start_countdown_timer();
+                }
+            }
+            TimerCommand::Undo => inner.step_history(History::undo),
+            TimerCommand::Redo => inner.step_history(History::redo),
+        }
+        if undoable && inner.snapshot() != state {
+            inner.record(state);
+        }
+    }
+}
+
+impl EngineInner {
+    fn snapshot(&self) -> TimerSnapshot {
+        self.state.borrow().clone()
+    }
+
+    /// Mutate the state without holding the borrow across anything else
+    fn update(&self, f: impl FnOnce(&mut TimerSnapshot)) {
+        f(&mut self.state.borrow_mut());
+    }
+
+    /// Run `f`, then let listeners hear what it did, unless something
+    /// outside is still under way
+    fn batch(&self, f: impl FnOnce()) {
+        self.busy.set(self.busy.get() + 1);
+        f();
+        self.busy.set(self.busy.get() - 1);
+        if self.busy.get() == 0 {
+            self.deliver();
+        }
+    }
+
+    fn emit(&self, event: TimerEvent) {
+        self.pending.borrow_mut().push_back(event);
+    }
+
+    /// Hand each pending event to every listener, in order. Listeners are
+    /// taken out meanwhile, so they may subscribe or send commands; events
+    /// those cause join the queue and are delivered by this same loop.
+    fn deliver(&self) {
+        if self.delivering.replace(true) {
+            return;
+        }
+        loop {
+            let next = self.pending.borrow_mut().pop_front();
+            let Some(event) = next else {
+                break;
+            };
+            let mut listeners = self.listeners.take();
+            for listener in &mut listeners {
+                listener(&event);
+            }
+            let added = self.listeners.replace(listeners);
+            self.listeners.borrow_mut().extend(added);
+        }
+        self.delivering.set(false);
+    }
+
+    fn pause(&self) {
+        let state = self.snapshot();
+        if state.paused || state.blinking {
+            return;
+        }
+        self.update(|s| s.paused = true);
+        cancel(&self.countdown_timer_handle);
+        self.emit(TimerEvent::Paused {
+            remaining: state.remaining,
+        });
+    }
+
+    fn resume(self: &Rc<Self>) {
+        let state = self.snapshot();
+        if !state.paused {
+            return;
+        }
+        self.update(|s| s.paused = false);
+        self.emit(TimerEvent::Resumed {
+            remaining: state.remaining,
+        });
+        if !state.blinking {
+            self.start_countdown_timer();
+        }
+    }
+
+    /// Keep `before` to undo to. Time entry counts as what cancelling it
+    /// would leave: the countdown it paused, running again.
+    fn record(&self, before: TimerSnapshot) {
+        let before = TimerSnapshot {
+            editing: false,
+            paused: before.paused && (!before.editing || before.blinking),
+            ..before
+        };
+        self.history.borrow_mut().record(before);
+        self.show_history();
+    }
+
+    /// Go to the state `step` takes from the history, if there is one
+    fn step_history(
+        self: &Rc<Self>,
+        step: fn(&mut History, TimerSnapshot) -> Option<TimerSnapshot>,
+    ) {
+        let current = self.snapshot();
+        if current.editing {
+            return;
+        }
+        let Some(target) = step(&mut self.history.borrow_mut(), current) else {
+            return;
+        };
+        self.restore(target);
+        self.show_history();
+    }
+
+    /// Carry on from `target` as if it had never been left
+    fn restore(self: &Rc<Self>, target: TimerSnapshot) {
+        cancel(&self.countdown_timer_handle);
+        cancel(&self.blink_timer_handle);
+        self.update(|s| {
+            *s = TimerSnapshot {
+                blinking: false,
+                blink_visible: true,
+                ..target.clone()
+            }
+        });
+        self.emit(TimerEvent::Reset {
+            remaining: target.remaining,
+        });
+        if target.blinking {
+            self.trigger_blink_timer();
+        }
+        if !target.paused {
+            self.start_countdown_timer();
+        }
+    }
+
+    fn show_history(&self) {
+        let (can_undo, can_redo) = {
+            let history = self.history.borrow();
+            (history.can_undo(), history.can_redo())
+        };
+        self.update(|s| {
+            s.can_undo = can_undo;
+            s.can_redo = can_redo;
+        });
+    }
+
+    /// Leave time-entry mode, remember the duration and restart from it
+    fn set_duration(self: &Rc<Self>, seconds: i32) {
+        cancel(&self.countdown_timer_handle);
+        cancel(&self.blink_timer_handle);
+        self.update(|s| {
+            s.remaining = seconds;
+            s.reset_time = seconds;
+            s.editing = false;
+            s.blinking = false;
+            s.blink_visible = true;
+            s.paused = false;
+        });
+        self.emit(TimerEvent::Started { seconds });
+        self.start_countdown_timer();
+    }
+
+    /// Start or restart the main countdown timer
+    fn start_countdown_timer(self: &Rc<Self>) {
+        self.started.set(true);
+        cancel(&self.countdown_timer_handle);
+
+        // Intervals hold the engine weakly so dropping it stops them
+        let engine = Rc::downgrade(self);
+        let interval_ms = self.settings.borrow().countdown_interval_ms;
+        let handle = self.provider.set_interval(
+            interval_ms,
+            Box::new(move || {
+                if let Some(engine) = engine.upgrade() {
+                    engine.batch(|| engine.count_down());
+                }
+            }),
+        );
+        *self.countdown_timer_handle.borrow_mut() = Some(handle);
+    }
+
+    /// One countdown step
+    fn count_down(self: &Rc<Self>) {
+        let minimum = self.settings.borrow().minimum_seconds();
+        let before = self.snapshot();
+        let result = update_countdown(before.remaining, minimum);
+        self.update(|s| s.remaining = result.remaining);
+
+        if result.remaining != before.remaining {
+            self.emit(TimerEvent::Tick {
+                remaining: result.remaining,
+            });
+            let settings = self.settings.borrow();
+            if let Some(stage) =
+                entered_warning_stage(&settings.warnings, before.remaining, result.remaining)
+            {
+                self.emit(TimerEvent::WarningReached {
+                    at_seconds: stage.at_seconds,
+                });
+            }
+        }
+        if result.should_blink && !before.blinking {
+            self.emit(TimerEvent::Expired);
+        }
+        if result.remaining != before.remaining
+            && result.remaining < 0
+            && result.remaining % 60 == 0
+        {
+            self.emit(TimerEvent::OverrunMinute {
+                minutes: -result.remaining / 60,
+            });
+        }
+
+        if result.should_stop {
+            // Stop the countdown timer when limit (e.g. -59:59) is reached
+            cancel(&self.countdown_timer_handle);
+            self.emit(TimerEvent::Stopped {
+                remaining: result.remaining,
+            });
+        }
+
+        if result.should_blink {
+            self.trigger_blink_timer();
+            self.refresh_blink_interval();
+        }
+    }
+
+    /// Restart blinking if the settings or an escalation stage call for
+    /// another period than the running one
+    fn refresh_blink_interval(self: &Rc<Self>) {
+        let state = self.snapshot();
+        let interval_ms = self.settings.borrow().blink_interval_at(state.remaining);
+        if !state.blinking || interval_ms == self.blink_interval_ms.get() {
+            return;
+        }
+        cancel(&self.blink_timer_handle);
+        self.update(|s| s.blinking = false);
+        self.trigger_blink_timer();
+    }
+
+    /// Start blinking if not already blinking
+    fn trigger_blink_timer(self: &Rc<Self>) {
+        if self.snapshot().blinking {
+            return;
+        }
+        self.update(|s| {
+            s.blinking = true;
+            s.blink_visible = true;
+        });
+        let engine = Rc::downgrade(self);
+        let interval_ms = self
+            .settings
+            .borrow()
+            .blink_interval_at(self.snapshot().remaining);
+        self.blink_interval_ms.set(interval_ms);
+        let handle = self.provider.set_interval(
+            interval_ms,
+            Box::new(move || {
+                if let Some(engine) = engine.upgrade() {
+                    engine.batch(|| {
+                        engine.update(|s| s.blink_visible = toggle_blink(s.blink_visible))
+                    });
+                }
+            }),
+        );
+        *self.blink_timer_handle.borrow_mut() = Some(handle);
+    }
+}
+
+fn cancel(handle: &RefCell<Option<Box<dyn TimerHandle>>>) {
+    // Take first: cancelling must not hold the borrow
+    let taken = handle.borrow_mut().take();
+    if let Some(mut handle) = taken {
+        handle.cancel();
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::config::{BLINK_INTERVAL_MS, COUNTDOWN_INTERVAL_MS, INITIAL_SECONDS};
+    use crate::settings::EscalationStage;
+    use crate::timer_provider::tests::FakeProvider;
+    use crate::timer_provider::ManualClockProvider;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Engine on a `FakeProvider`, which runs each new interval once at once
+    fn engine(initial_seconds: i32) -> (TimerEngine, Rc<RefCell<Vec<u32>>>) {
+        let fake = FakeProvider::new();
+        let calls = fake.calls.clone();
+        let settings = Settings {
+            default_seconds: initial_seconds,
+            ..Settings::default()
+        };
+        (TimerEngine::new(Rc::new(fake), settings), calls)
+    }
+
+    #[test]
+    fn test_start_countdown_no_blink() {
+        let (engine, calls) = engine(5);
+        engine.apply(TimerCommand::Attach {
+            saved_seconds: None,
+        });
+
+        // FakeProvider triggers callback immediately: remaining should decrement
+        assert_eq!(engine.snapshot().remaining, 4);
+        // Only countdown interval scheduled
+        assert_eq!(&*calls.borrow(), &[COUNTDOWN_INTERVAL_MS]);
+        // No blink timer scheduled
+        assert!(engine.inner.blink_timer_handle.borrow().is_none());
+    }
+
+    #[test]
+    fn test_start_countdown_with_blink() {
+        let (engine, calls) = engine(0);
+        engine.apply(TimerCommand::Attach {
+            saved_seconds: None,
+        });
+
+        // FakeProvider triggers countdown then blink callbacks
+        let state = engine.snapshot();
+        assert_eq!(state.remaining, -1);
+        assert!(state.blinking);
+        // two intervals scheduled: countdown and blink
+        assert_eq!(
+            &*calls.borrow(),
+            &[COUNTDOWN_INTERVAL_MS, BLINK_INTERVAL_MS]
+        );
+        // Blink handle is Some
+        assert!(engine.inner.blink_timer_handle.borrow().is_some());
+    }
+
+    #[test]
+    fn test_trigger_blink_timer_only_once() {
+        let (engine, calls) = engine(5);
+
+        // First call schedules blink
+        engine.inner.trigger_blink_timer();
+        // blinking has started; fake provider callback toggled visibility off immediately
+        let state = engine.snapshot();
+        assert!(state.blinking);
+        assert!(!state.blink_visible);
+        assert_eq!(&*calls.borrow(), &[BLINK_INTERVAL_MS]);
+        assert!(engine.inner.blink_timer_handle.borrow().is_some());
+
+        // Second call does nothing: no new schedule and handle remains Some
+        engine.inner.trigger_blink_timer();
+        assert_eq!(&*calls.borrow(), &[BLINK_INTERVAL_MS]);
+        assert!(engine.inner.blink_timer_handle.borrow().is_some());
+    }
+
+    #[test]
+    fn test_attach_uses_saved_seconds_once() {
+        let (engine, calls) = engine(INITIAL_SECONDS);
+        engine.apply(TimerCommand::Attach {
+            saved_seconds: Some(90),
+        });
+        assert_eq!(engine.snapshot().remaining, 89);
+        assert_eq!(engine.snapshot().reset_time, 90);
+
+        // A reloaded window attaching again changes nothing
+        engine.apply(TimerCommand::Attach {
+            saved_seconds: Some(300),
+        });
+        assert_eq!(engine.snapshot().remaining, 89);
+        assert_eq!(&*calls.borrow(), &[COUNTDOWN_INTERVAL_MS]);
+    }
+
+    #[test]
+    fn test_reset_not_blinking() {
+        let (engine, calls) = engine(999);
+        engine.apply(TimerCommand::Reset);
+
+        // Should schedule countdown interval exactly once
+        assert_eq!(&*calls.borrow(), &[COUNTDOWN_INTERVAL_MS]);
+        // FakeProvider triggers immediately: remaining decremented from the reset time
+        let state = engine.snapshot();
+        assert_eq!(state.remaining, 998);
+        // Blink state reset
+        assert!(!state.blinking);
+        assert!(state.blink_visible);
+        // No blink handle present
+        assert!(engine.inner.blink_timer_handle.borrow().is_none());
+    }
+
+    #[test]
+    fn test_reset_blinking() {
+        let (engine, calls) = engine(INITIAL_SECONDS);
+        // simulate blinking started
+        engine.inner.trigger_blink_timer();
+        // reset call history
+        calls.borrow_mut().clear();
+
+        // Now resetting should cancel blink and restart countdown
+        engine.apply(TimerCommand::Reset);
+
+        // Blink cancelled: handle taken
+        assert!(engine.inner.blink_timer_handle.borrow().is_none());
+        // Blink signal cleared
+        let state = engine.snapshot();
+        assert!(!state.blinking);
+        assert!(state.blink_visible);
+        // New countdown scheduled
+        assert_eq!(&*calls.borrow(), &[COUNTDOWN_INTERVAL_MS]);
+        // FakeProvider immediate callback: remaining from INITIAL_SECONDS
+        assert_eq!(state.remaining, INITIAL_SECONDS - 1);
+    }
+
+    #[test]
+    fn test_reset_ignored_while_paused() {
+        let (engine, calls) = engine(100);
+        engine.apply(TimerCommand::Attach {
+            saved_seconds: None,
+        });
+        engine.apply(TimerCommand::Pause);
+        engine.apply(TimerCommand::Reset);
+        assert_eq!(engine.snapshot().remaining, 99);
+        assert_eq!(&*calls.borrow(), &[COUNTDOWN_INTERVAL_MS]);
+    }
+
+    #[test]
+    fn test_pause_and_resume() {
+        let (engine, calls) = engine(100);
+        engine.apply(TimerCommand::Attach {
+            saved_seconds: None,
+        });
+        engine.apply(TimerCommand::TogglePause);
+        assert!(engine.snapshot().paused);
+        assert!(engine.inner.countdown_timer_handle.borrow().is_none());
+
+        engine.apply(TimerCommand::TogglePause);
+        let state = engine.snapshot();
+        assert!(!state.paused);
+        assert_eq!(state.remaining, 98);
+        assert_eq!(
+            &*calls.borrow(),
+            &[COUNTDOWN_INTERVAL_MS, COUNTDOWN_INTERVAL_MS]
+        );
+    }
+
+    #[test]
+    fn test_pause_ignored_while_blinking() {
+        let (engine, _calls) = engine(0);
+        engine.apply(TimerCommand::Attach {
+            saved_seconds: None,
+        });
+        engine.apply(TimerCommand::Pause);
+        assert!(!engine.snapshot().paused);
+    }
+
+    #[test]
+    fn test_edit_pauses_and_cancel_resumes() {
+        let (engine, _calls) = engine(100);
+        engine.apply(TimerCommand::Attach {
+            saved_seconds: None,
+        });
+        engine.apply(TimerCommand::EnterEdit);
+        let state = engine.snapshot();
+        assert!(state.editing);
+        assert!(state.paused);
+
+        engine.apply(TimerCommand::CancelEdit);
+        let state = engine.snapshot();
+        assert!(!state.editing);
+        assert!(!state.paused);
+        assert_eq!(state.remaining, 98);
+    }
+
+    #[test]
+    fn test_set_duration_restarts_from_new_time() {
+        let (engine, calls) = engine(0);
+        engine.apply(TimerCommand::Attach {
+            saved_seconds: None,
+        });
+        engine.apply(TimerCommand::EnterEdit);
+        calls.borrow_mut().clear();
+
+        engine.apply(TimerCommand::SetDuration { seconds: 300 });
+        let state = engine.snapshot();
+        assert_eq!(state.remaining, 299);
+        assert_eq!(state.reset_time, 300);
+        assert!(!state.editing);
+        assert!(!state.paused);
+        assert!(!state.blinking);
+        assert!(engine.inner.blink_timer_handle.borrow().is_none());
+        assert_eq!(&*calls.borrow(), &[COUNTDOWN_INTERVAL_MS]);
+    }
+
+    #[test]
+    fn test_settings_default_becomes_reset_time() {
+        let (engine, _calls) = engine(100);
+        engine.apply(TimerCommand::Attach {
+            saved_seconds: None,
+        });
+        engine.apply_settings(Settings {
+            default_seconds: 600,
+            ..Settings::default()
+        });
+        let state = engine.snapshot();
+        // The running countdown is left alone
+        assert_eq!(state.remaining, 99);
+        assert_eq!(state.reset_time, 600);
+    }
+
+    #[test]
+    fn test_settings_default_keeps_set_duration() {
+        let (engine, _calls) = engine(100);
+        engine.apply(TimerCommand::Attach {
+            saved_seconds: None,
+        });
+        assert!(!engine.snapshot().duration_set);
+        // Set, even though it is what the default was
+        engine.apply(TimerCommand::SetDuration { seconds: 100 });
+        engine.apply_settings(Settings {
+            default_seconds: 600,
+            ..Settings::default()
+        });
+        // A reset still returns to the duration that was set
+        let state = engine.snapshot();
+        assert!(state.duration_set);
+        assert_eq!(state.reset_time, 100);
+    }
+
+    #[test]
+    fn test_settings_restart_intervals_at_new_period() {
+        let (engine, calls) = engine(0);
+        engine.apply(TimerCommand::Attach {
+            saved_seconds: None,
+        });
+        calls.borrow_mut().clear();
+
+        engine.apply_settings(Settings {
+            default_seconds: 0,
+            countdown_interval_ms: 2000,
+            blink_interval_ms: 250,
+            ..Settings::default()
+        });
+        assert_eq!(&*calls.borrow(), &[2000, 250]);
+        assert!(engine.snapshot().blinking);
+    }
+
+    #[test]
+    fn test_settings_overrun_limit() {
+        let (engine, calls) = engine(0);
+        engine.apply_settings(Settings {
+            default_seconds: 0,
+            overrun_limit_seconds: 0,
+            ..Settings::default()
+        });
+        engine.apply(TimerCommand::Attach {
+            saved_seconds: None,
+        });
+        // Stopped at once: the limit is zero
+        let state = engine.snapshot();
+        assert_eq!(state.remaining, 0);
+        assert!(state.blinking);
+
+        // Extending the limit lets the stopped countdown carry on
+        calls.borrow_mut().clear();
+        engine.apply_settings(Settings {
+            default_seconds: 0,
+            overrun_limit_seconds: 60,
+            ..Settings::default()
+        });
+        assert_eq!(engine.snapshot().remaining, -1);
+        assert_eq!(&*calls.borrow(), &[COUNTDOWN_INTERVAL_MS]);
+    }
+
+    #[test]
+    fn test_escalation_speeds_up_blinking() {
+        let fake = FakeProvider::new();
+        let calls = fake.calls.clone();
+        let settings = Settings {
+            default_seconds: 0,
+            escalation: vec![EscalationStage {
+                after_seconds: 5,
+                blink_interval_ms: 100,
+                ..EscalationStage::default()
+            }],
+            ..Settings::default()
+        };
+        let engine = TimerEngine::new(Rc::new(fake), settings.clone());
+        engine.apply(TimerCommand::Attach {
+            saved_seconds: Some(-5),
+        });
+        // The first step reaches -6, into the stage, and blinks at its pace
+        assert_eq!(engine.snapshot().remaining, -6);
+        assert_eq!(&*calls.borrow(), &[COUNTDOWN_INTERVAL_MS, 100]);
+
+        // Dropping the stage slows it again
+        calls.borrow_mut().clear();
+        engine.apply_settings(Settings {
+            escalation: Vec::new(),
+            ..settings
+        });
+        assert_eq!(&*calls.borrow(), &[BLINK_INTERVAL_MS]);
+    }
+
+    /// Engine on a `FakeProvider`, attached at a default of `initial_seconds`
+    pub(crate) fn attached(initial_seconds: i32) -> TimerEngine {
+        let (engine, _) = engine(initial_seconds);
+        engine.apply(TimerCommand::Attach {
+            saved_seconds: None,
+        });
+        engine
+    }
+
+    #[test]
+    fn test_snooze_only_once_up() {
+        let engine = attached(1500);
+        engine.apply(TimerCommand::Snooze { seconds: None });
+        assert_eq!(engine.snapshot().remaining, 1499);
+
+        let engine = attached(0);
+        assert!(engine.snapshot().blinking);
+        engine.apply(TimerCommand::Snooze { seconds: None });
+        let state = engine.snapshot();
+        assert!(!state.blinking);
+        assert!(engine.inner.blink_timer_handle.borrow().is_none());
+        // The set duration stays for the next reset; the fake provider
+        // ticks once
+        assert_eq!((state.remaining, state.reset_time), (299, 0));
+        assert!(state.can_undo);
+
+        let engine = attached(0);
+        engine.apply(TimerCommand::Snooze { seconds: Some(60) });
+        assert_eq!(engine.snapshot().remaining, 59);
+    }
+
+    #[test]
+    fn test_undo_and_redo_new_duration() {
+        let engine = attached(1500);
+        assert!(!engine.snapshot().can_undo);
+        engine.apply(TimerCommand::SetDuration { seconds: 300 });
+        assert!(engine.snapshot().can_undo);
+
+        // The countdown carries on from where it was; each restart ticks
+        // once on the fake provider
+        engine.apply(TimerCommand::Undo);
+        let state = engine.snapshot();
+        assert_eq!((state.remaining, state.reset_time), (1498, 1500));
+        assert!(!state.can_undo);
+        assert!(state.can_redo);
+
+        engine.apply(TimerCommand::Redo);
+        let state = engine.snapshot();
+        assert_eq!((state.remaining, state.reset_time), (298, 300));
+        assert!(state.can_undo);
+        assert!(!state.can_redo);
+    }
+
+    #[test]
+    fn test_undo_restores_blinking_and_pause() {
+        let engine = attached(0);
+        engine.apply(TimerCommand::SetDuration { seconds: 600 });
+        assert!(!engine.snapshot().blinking);
+        engine.apply(TimerCommand::Undo);
+        let state = engine.snapshot();
+        assert!(state.blinking);
+        assert_eq!((state.remaining, state.reset_time), (-2, 0));
+
+        let engine = attached(1500);
+        engine.apply(TimerCommand::TogglePause);
+        // Pausing is not worth undoing
+        assert!(!engine.snapshot().can_undo);
+        engine.apply(TimerCommand::Start);
+        engine.apply(TimerCommand::Undo);
+        let state = engine.snapshot();
+        assert!(state.paused);
+        assert_eq!(state.remaining, 1499);
+    }
+
+    #[test]
+    fn test_undo_time_entry() {
+        let engine = attached(1500);
+        engine.apply(TimerCommand::EnterEdit);
+        // Undo is the input's own while entering time
+        engine.apply(TimerCommand::Undo);
+        assert!(engine.snapshot().editing);
+
+        engine.apply(TimerCommand::SetDuration { seconds: 60 });
+        engine.apply(TimerCommand::Undo);
+        // Back counting, as cancelling the entry would have left it
+        let state = engine.snapshot();
+        assert!(!state.editing);
+        assert!(!state.paused);
+        assert_eq!(state.remaining, 1498);
+    }
+
+    /// Engine on a `ManualClockProvider`, started from `seconds`
+    fn on_clock(seconds: i32) -> (TimerEngine, Rc<ManualClockProvider>) {
+        let clock = Rc::new(ManualClockProvider::new());
+        let engine = TimerEngine::new(clock.clone(), Settings::default());
+        engine.apply(TimerCommand::Attach {
+            saved_seconds: Some(seconds),
+        });
+        (engine, clock)
+    }
+
+    const SECOND: u64 = COUNTDOWN_INTERVAL_MS as u64;
+    const BLINK: u64 = BLINK_INTERVAL_MS as u64;
+
+    #[test]
+    fn test_counts_down_step_by_step() {
+        let (engine, clock) = on_clock(5);
+        clock.advance(3 * SECOND);
+        assert_eq!(engine.snapshot().remaining, 2);
+        clock.advance(2 * SECOND);
+        assert_eq!(engine.snapshot().remaining, 0);
+        assert!(!engine.snapshot().blinking);
+        clock.advance(SECOND);
+        assert_eq!(engine.snapshot().remaining, -1);
+        assert!(engine.snapshot().blinking);
+    }
+
+    #[test]
+    fn test_blink_cadence() {
+        let (engine, clock) = on_clock(0);
+        clock.advance(SECOND);
+        let mut seen = vec![engine.snapshot().blink_visible];
+        for _ in 0..4 {
+            clock.advance(BLINK);
+            seen.push(engine.snapshot().blink_visible);
+        }
+        assert_eq!(seen, [true, false, true, false, true]);
+        // Counting on past zero meanwhile
+        assert_eq!(engine.snapshot().remaining, -3);
+    }
+
+    #[test]
+    fn test_pause_mid_countdown() {
+        let (engine, clock) = on_clock(10);
+        clock.advance(SECOND * 5 / 2);
+        engine.apply(TimerCommand::Pause);
+        assert_eq!(clock.next_due_ms(), None);
+        clock.advance(10 * SECOND);
+        assert_eq!(engine.snapshot().remaining, 8);
+
+        // A whole step from resuming, not what was left of the last one
+        engine.apply(TimerCommand::Resume);
+        clock.advance(SECOND - 1);
+        assert_eq!(engine.snapshot().remaining, 8);
+        clock.advance(1);
+        assert_eq!(engine.snapshot().remaining, 7);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_command_wire_format() {
+        let command = TimerCommand::SetDuration { seconds: 300 };
+        let json = serde_json::to_string(&command).unwrap();
+        assert_eq!(json, r#"{"command":"set_duration","seconds":300}"#);
+        assert_eq!(
+            serde_json::from_str::<TimerCommand>(&json).unwrap(),
+            command
+        );
+        assert_eq!(
+            serde_json::from_str::<TimerCommand>(r#"{"command":"toggle_pause"}"#).unwrap(),
+            TimerCommand::TogglePause
+        );
+    }
+}
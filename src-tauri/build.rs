@@ -1,3 +1,12 @@
 fn main() {
+    // `--command` looks for the running app's folder before Tauri has
+    // loaded this config, so the identifier naming it is built in
+    let config = std::fs::read_to_string("tauri.conf.json").expect("tauri.conf.json is readable");
+    let config: serde_json::Value = serde_json::from_str(&config).expect("tauri.conf.json is JSON");
+    let identifier = config["identifier"]
+        .as_str()
+        .expect("tauri.conf.json has an identifier");
+    println!("cargo:rustc-env=COUNTDOWN_IDENTIFIER={identifier}");
+    println!("cargo:rerun-if-changed=tauri.conf.json");
     tauri_build::build()
 }
@@ -0,0 +1,68 @@
+//! Alerts beyond the display when time runs low or is up. The sound is
+//! played by the timer window; the backend asks the window manager for
+//! attention.
+
+use countdown_core::{Settings, TimerEvent};
+use tauri::{AppHandle, Manager, UserAttentionType};
+
+/// The attention `event` calls for: time up, or reaching a warning stage
+/// that alerts
+fn attention(event: &TimerEvent, settings: &Settings) -> Option<UserAttentionType> {
+    match event {
+        TimerEvent::Expired => Some(UserAttentionType::Critical),
+        TimerEvent::WarningReached { at_seconds } => settings
+            .warnings
+            .iter()
+            .any(|stage| stage.at_seconds == *at_seconds && stage.alert)
+            .then_some(UserAttentionType::Informational),
+        _ => None,
+    }
+}
+
+pub fn on_event(app: &AppHandle, event: &TimerEvent, settings: &Settings) {
+    if !settings.alerts.request_attention {
+        return;
+    }
+    let Some(attention) = attention(event, settings) else {
+        return;
+    };
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.request_user_attention(Some(attention));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use countdown_core::WarningStage;
+
+    #[test]
+    fn test_attention_on_time_up_and_alerting_stages() {
+        let settings = Settings {
+            warnings: vec![
+                WarningStage {
+                    at_seconds: 60,
+                    alert: true,
+                    ..WarningStage::default()
+                },
+                WarningStage {
+                    at_seconds: 30,
+                    ..WarningStage::default()
+                },
+            ],
+            ..Settings::default()
+        };
+        let attention = |event| attention(&event, &settings);
+        assert!(matches!(
+            attention(TimerEvent::Expired),
+            Some(UserAttentionType::Critical)
+        ));
+        assert!(matches!(
+            attention(TimerEvent::WarningReached { at_seconds: 60 }),
+            Some(UserAttentionType::Informational)
+        ));
+        // Reached, but this stage does not alert
+        assert!(attention(TimerEvent::WarningReached { at_seconds: 30 }).is_none());
+        assert!(attention(TimerEvent::Tick { remaining: 59 }).is_none());
+    }
+}
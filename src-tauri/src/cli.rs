@@ -0,0 +1,145 @@
+//! `countdown --command <name> [mm:ss]` sends one command to the running
+//! timer through the control API, prints the answer and exits, rather than
+//! starting a second timer. The names are those in
+//! `dispatch::COMMANDS`; without one, it lists them.
+
+use crate::control;
+use crate::portable::DataDirs;
+use countdown_core::dispatch::{self, CommandError, COMMANDS};
+use countdown_core::TimerCommand;
+use std::io::{self, Read, Write};
+
+/// Command-line flag naming the command to send
+pub const FLAG: &str = "--command";
+
+/// Handle `--command` if it is among `args`, returning the exit code
+pub fn run(args: &[String]) -> Option<i32> {
+    let position = args.iter().position(|arg| arg == FLAG)?;
+    let rest = &args[position + 1..];
+    let Some(name) = rest.first() else {
+        print_usage();
+        return Some(2);
+    };
+    let command = match parse(name, rest.get(1).map(String::as_str)) {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("countdown: {err}");
+            return Some(2);
+        }
+    };
+    match send(&command) {
+        Ok((status, body)) => {
+            println!("{body}");
+            Some(if status == 200 { 0 } else { 1 })
+        }
+        Err(err) => {
+            eprintln!("countdown: cannot reach the timer, is it running? ({err})");
+            Some(1)
+        }
+    }
+}
+
+fn parse(name: &str, argument: Option<&str>) -> Result<TimerCommand, CommandError> {
+    let command = dispatch::parse(name, argument)?;
+    dispatch::validate(&command)?;
+    Ok(command)
+}
+
+fn print_usage() {
+    eprintln!("usage: countdown {FLAG} <command> [argument]\n\ncommands:");
+    for spec in COMMANDS {
+        let argument = match (spec.argument, spec.optional) {
+            (Some(argument), true) => format!("[{argument}]"),
+            (Some(argument), false) => argument.to_string(),
+            (None, _) => String::new(),
+        };
+        eprintln!("  {:<14}{argument:<9}{}", spec.name, spec.summary);
+    }
+}
+
+/// `POST /command` with `command` as its body, and the token if the port
+/// needs one
+fn request(command: &TimerCommand, token: Option<&str>) -> String {
+    let body = serde_json::to_string(command).expect("commands serialize");
+    let authorization = token
+        .map(|token| format!("Authorization: Bearer {token}\r\n"))
+        .unwrap_or_default();
+    format!(
+        "POST /command HTTP/1.1\r\nHost: localhost\r\n{authorization}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// The status and body of a response
+fn parse_response(response: &str) -> Option<(u16, &str)> {
+    let (head, body) = response.split_once("\r\n\r\n")?;
+    let status = head.split_whitespace().nth(1)?.parse().ok()?;
+    Some((status, body))
+}
+
+fn exchange(mut stream: impl Read + Write, request: &str) -> io::Result<(u16, String)> {
+    stream.write_all(request.as_bytes())?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    parse_response(&response)
+        .map(|(status, body)| (status, body.to_string()))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed response"))
+}
+
+/// Over the socket, or where there is none, the port the running timer was
+/// given in `COUNTDOWN_CONTROL_PORT`
+fn send(command: &TimerCommand) -> io::Result<(u16, String)> {
+    let dir = DataDirs::resolve()?.control;
+    #[cfg(unix)]
+    {
+        let stream = std::os::unix::net::UnixStream::connect(dir.join(control::SOCKET_FILE))?;
+        exchange(stream, &request(command, None))
+    }
+    #[cfg(not(unix))]
+    {
+        let port = std::env::var(control::PORT_ENV)
+            .ok()
+            .and_then(|port| port.parse::<u16>().ok())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("set {} as it is for the timer", control::PORT_ENV),
+                )
+            })?;
+        let token = std::fs::read_to_string(dir.join(control::TOKEN_FILE))?;
+        let stream = std::net::TcpStream::connect((std::net::Ipv4Addr::LOCALHOST, port))?;
+        exchange(stream, &request(command, Some(token.trim())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request() {
+        let sent = request(&TimerCommand::Snooze { seconds: Some(60) }, None);
+        assert!(sent.starts_with("POST /command HTTP/1.1\r\n"));
+        assert!(sent.contains("Content-Length: 33\r\n"));
+        assert!(sent.ends_with("\r\n\r\n{\"command\":\"snooze\",\"seconds\":60}"));
+        assert!(!sent.contains("Authorization"));
+        let sent = request(&TimerCommand::Reset, Some("secret"));
+        assert!(sent.contains("\r\nAuthorization: Bearer secret\r\n"));
+    }
+
+    #[test]
+    fn test_parse_refuses_before_sending() {
+        assert_eq!(parse("reset", None), Ok(TimerCommand::Reset));
+        assert!(parse("snooze", Some("0")).is_err());
+        assert!(parse("set_duration", Some("60:00")).is_err());
+    }
+
+    #[test]
+    fn test_parse_response() {
+        assert_eq!(
+            parse_response("HTTP/1.1 400 Bad Request\r\nConnection: close\r\n\r\n{}"),
+            Some((400, "{}"))
+        );
+        assert_eq!(parse_response("garbage"), None);
+    }
+}
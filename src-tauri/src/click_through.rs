@@ -0,0 +1,55 @@
+//! Click-through: the timer window lets the mouse through to whatever is
+//! beneath it, so it can sit over an editor without swallowing clicks or
+//! being reset by one. Toggled from the tray, since a window that ignores
+//! the mouse cannot be clicked to turn it back off.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::menu::CheckMenuItem;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Event carrying whether click-through is on, whenever it changes
+const CLICK_THROUGH_EVENT: &str = "click-through-changed";
+
+/// Managed as Tauri state. Always off at start, so the window can never
+/// come up unreachable.
+#[derive(Default)]
+pub struct ClickThrough {
+    enabled: AtomicBool,
+    /// The tray item showing the state, if there is a tray
+    menu_item: Mutex<Option<CheckMenuItem>>,
+}
+
+impl ClickThrough {
+    pub fn enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Keep `item` ticked while click-through is on
+    pub fn attach_menu_item(&self, item: CheckMenuItem) {
+        *self.menu_item.lock().unwrap() = Some(item);
+    }
+
+    fn show(&self, enabled: bool) {
+        if let Some(item) = self.menu_item.lock().unwrap().as_ref() {
+            let _ = item.set_checked(enabled);
+        }
+    }
+}
+
+/// Turn click-through on or off for the timer window
+pub fn set(app: &AppHandle, enabled: bool) -> tauri::Result<()> {
+    let state = app.state::<ClickThrough>();
+    if let Some(window) = app.get_webview_window("main") {
+        window.set_ignore_cursor_events(enabled)?;
+    }
+    state.enabled.store(enabled, Ordering::Relaxed);
+    state.show(enabled);
+    let _ = app.emit(CLICK_THROUGH_EVENT, enabled);
+    Ok(())
+}
+
+pub fn toggle(app: &AppHandle) -> tauri::Result<()> {
+    let enabled = app.state::<ClickThrough>().enabled();
+    set(app, !enabled)
+}
@@ -0,0 +1,86 @@
+//! Tauri commands for the windows. Timer commands all go through
+//! `timer_dispatch`; windows also receive every new state as a
+//! `timer-state` event.
+
+use crate::click_through::ClickThrough;
+use crate::lock;
+use crate::settings::{self, SettingsStatus, SettingsStore};
+use crate::settings_window;
+use crate::state_store::StateStore;
+use crate::timer_runtime::TimerRuntime;
+use crate::window_state;
+use countdown_core::config::MAXIMUM_SECONDS;
+use countdown_core::dispatch::Dispatched;
+use countdown_core::{Settings, TimerCommand, TimerSnapshot};
+use tauri::{AppHandle, Manager, State};
+
+/// Apply a timer command, from a key, a click or the window starting up.
+/// Attaching starts the timer, if no window has yet, from the duration the
+/// backend saved, or failing that the window's `saved_seconds`.
+#[tauri::command]
+pub fn timer_dispatch(
+    command: TimerCommand,
+    timer: State<'_, TimerRuntime>,
+    stored: State<'_, StateStore>,
+) -> Result<Dispatched, String> {
+    let command = match command {
+        TimerCommand::Attach { saved_seconds } => TimerCommand::Attach {
+            saved_seconds: stored
+                .duration()
+                .or(saved_seconds.filter(|seconds| (0..=MAXIMUM_SECONDS).contains(seconds))),
+        },
+        command => command,
+    };
+    timer.send(command).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub fn timer_state(timer: State<'_, TimerRuntime>) -> TimerSnapshot {
+    timer.snapshot()
+}
+
+/// The settings in force and any problem with the settings file; windows
+/// also receive changes as a `settings-changed` event
+#[tauri::command]
+pub fn get_settings(settings: State<'_, SettingsStore>) -> SettingsStatus {
+    settings.status()
+}
+
+/// Write settings from the settings window and apply them at once
+#[tauri::command]
+pub fn save_settings(settings: Settings, app: AppHandle) -> Result<SettingsStatus, String> {
+    settings::save(&app, settings)
+}
+
+#[tauri::command]
+pub fn open_settings(app: AppHandle) -> Result<(), String> {
+    settings_window::open(&app).map_err(|err| err.to_string())
+}
+
+/// Whether the timer window lets clicks through; windows also receive
+/// changes as a `click-through-changed` event
+#[tauri::command]
+pub fn get_click_through(click_through: State<'_, ClickThrough>) -> bool {
+    click_through.enabled()
+}
+
+/// Whether the timer window is locked; windows also receive changes as a
+/// `lock-changed` event
+#[tauri::command]
+pub fn get_lock(app: AppHandle) -> bool {
+    lock::locked(&app)
+}
+
+#[tauri::command]
+pub fn set_lock(locked: bool, app: AppHandle) -> Result<(), String> {
+    lock::set(&app, locked).map_err(|err| err.to_string())
+}
+
+/// Step the timer window to its next larger or smaller preset size
+#[tauri::command]
+pub fn step_window_size(larger: bool, app: AppHandle) -> Result<(), String> {
+    let Some(window) = app.get_webview_window("main") else {
+        return Ok(());
+    };
+    window_state::step_size(&window, larger).map_err(|err| err.to_string())
+}
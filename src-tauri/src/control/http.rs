@@ -0,0 +1,179 @@
+//! Just enough HTTP/1.1 for the control API: one request per connection,
+//! `Content-Length` bodies only, no keep-alive.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+/// Bodies are a few bytes of JSON; anything larger is not a client of ours.
+const MAX_BODY_BYTES: usize = 16 * 1024;
+const MAX_HEADERS: usize = 64;
+
+#[derive(Debug)]
+pub struct Request {
+    pub method: String,
+    /// Path without the query string
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Case-insensitive header lookup
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn malformed(reason: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason.to_string())
+}
+
+/// Read a single request from the stream
+pub fn read_request(stream: impl Read) -> io::Result<Request> {
+    let mut reader = BufReader::new(stream);
+
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(malformed("malformed request line"));
+    };
+    let method = method.to_string();
+    let path = target.split('?').next().unwrap_or_default().to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(malformed("connection closed inside headers"));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if headers.len() == MAX_HEADERS {
+            return Err(malformed("too many headers"));
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            return Err(malformed("malformed header"));
+        };
+        headers.push((key.trim().to_string(), value.trim().to_string()));
+    }
+
+    let mut request = Request {
+        method,
+        path,
+        headers,
+        body: Vec::new(),
+    };
+
+    let length = match request.header("content-length") {
+        Some(value) => value
+            .parse::<usize>()
+            .map_err(|_| malformed("bad Content-Length"))?,
+        None => 0,
+    };
+    if length > MAX_BODY_BYTES {
+        return Err(malformed("body too large"));
+    }
+    request.body = vec![0; length];
+    reader.read_exact(&mut request.body)?;
+
+    Ok(request)
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
+
+/// Write a complete JSON response and let the connection close
+pub fn write_json(mut stream: impl Write, status: u16, body: &serde_json::Value) -> io::Result<()> {
+    let body = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {status} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        reason_phrase(status),
+        body.len(),
+    )?;
+    stream.flush()
+}
+
+/// Start a server-sent event stream; frames follow via `event_frame`
+pub fn write_event_stream_head(mut stream: impl Write) -> io::Result<()> {
+    stream.write_all(
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+    )?;
+    stream.flush()
+}
+
+/// A server-sent comment, which clients ignore; sent only to find out
+/// whether the client is still there
+pub const KEEP_ALIVE_FRAME: &str = ": keep-alive\n\n";
+
+/// One server-sent event. `data` must be a single line, which JSON is.
+pub fn event_frame(event: &str, data: &str) -> String {
+    format!("event: {event}\ndata: {data}\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_request_with_body() {
+        let raw = b"POST /duration?x=1 HTTP/1.1\r\nHost: localhost\r\nContent-Length: 16\r\n\r\n{\"seconds\":300}\n";
+        let request = read_request(&raw[..]).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/duration");
+        assert_eq!(request.header("HOST"), Some("localhost"));
+        assert_eq!(request.body, b"{\"seconds\":300}\n");
+    }
+
+    #[test]
+    fn test_read_request_without_body() {
+        let raw = b"GET /state HTTP/1.1\r\n\r\n";
+        let request = read_request(&raw[..]).unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/state");
+        assert!(request.body.is_empty());
+    }
+
+    #[test]
+    fn test_read_request_rejects_garbage() {
+        assert!(read_request(&b"\r\n\r\n"[..]).is_err());
+        assert!(read_request(&b"GET /state HTTP/1.1\r\nno colon\r\n\r\n"[..]).is_err());
+        assert!(read_request(&b"GET /state HTTP/1.1\r\nHost: x\r\n"[..]).is_err());
+    }
+
+    #[test]
+    fn test_read_request_rejects_large_body() {
+        let raw = b"POST /duration HTTP/1.1\r\nContent-Length: 999999\r\n\r\n";
+        assert!(read_request(&raw[..]).is_err());
+    }
+
+    #[test]
+    fn test_write_json() {
+        let mut out = Vec::new();
+        write_json(&mut out, 202, &serde_json::json!({"ok": true})).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("HTTP/1.1 202 Accepted\r\n"));
+        assert!(out.contains("Content-Length: 11\r\n"));
+        assert!(out.ends_with("\r\n\r\n{\"ok\":true}"));
+    }
+
+    #[test]
+    fn test_event_frame() {
+        assert_eq!(event_frame("tick", "{}"), "event: tick\ndata: {}\n\n");
+    }
+}
@@ -0,0 +1,627 @@
+//! Local control API for editor and shell integrations.
+//!
+//! JSON over HTTP on a Unix domain socket, `control.sock` in the app's local
+//! data directory (`DataDirs::control`), readable by the owner only. Setting
+//! `COUNTDOWN_CONTROL_PORT` also serves it on that port of 127.0.0.1, where
+//! every request must carry `Authorization: Bearer <token>` with the token
+//! from `control.token` next to the socket.
+//!
+//! Commands go to the same `TimerRuntime` the windows drive, through the
+//! same `dispatch`, so they apply exactly as the matching key or click
+//! would, and every window follows.
+
+mod http;
+
+use crate::portable::DataDirs;
+use crate::timer_runtime::TimerRuntime;
+use countdown_core::dispatch::{self, Outcome};
+use countdown_core::{TimerCommand, TimerEvent, TimerSnapshot};
+use http::Request;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::{Ipv4Addr, TcpListener};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+pub const SOCKET_FILE: &str = "control.sock";
+pub const TOKEN_FILE: &str = "control.token";
+pub const PORT_ENV: &str = "COUNTDOWN_CONTROL_PORT";
+
+/// Clients that stop sending mid-request are dropped after this long.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// An event stream with nothing to send writes a comment this often, so a
+/// client that went away while the timer was idle is noticed and its
+/// thread ends.
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// Timer state as served to clients: the snapshot minus the blink phase,
+/// which would otherwise turn every half second into an event
+#[derive(Serialize, Debug, Clone, PartialEq)]
+struct TimerState {
+    remaining: i32,
+    reset_time: i32,
+    paused: bool,
+    blinking: bool,
+    editing: bool,
+}
+
+impl From<&TimerSnapshot> for TimerState {
+    fn from(snapshot: &TimerSnapshot) -> Self {
+        Self {
+            remaining: snapshot.remaining,
+            reset_time: snapshot.reset_time,
+            paused: snapshot.paused,
+            blinking: snapshot.blinking,
+            editing: snapshot.editing,
+        }
+    }
+}
+
+/// Last broadcast timer state plus the event stream subscribers
+#[derive(Default)]
+pub struct ControlHub {
+    last: Mutex<Option<TimerState>>,
+    subscribers: Mutex<Vec<Sender<String>>>,
+}
+
+impl ControlHub {
+    /// Record the new timer state and broadcast it, as `tick` when only the
+    /// countdown advanced and `transition` otherwise
+    pub fn update_state(&self, snapshot: &TimerSnapshot) {
+        let state = TimerState::from(snapshot);
+        let mut current = self.last.lock().unwrap();
+        let event = match current.as_ref() {
+            Some(previous) if *previous == state => return,
+            Some(previous) if is_tick(previous, &state) => "tick",
+            _ => "transition",
+        };
+        let frame = http::event_frame(event, &serde_json::to_string(&state).unwrap());
+        *current = Some(state);
+        drop(current);
+        self.broadcast(frame);
+    }
+
+    /// Broadcast a timer event under its own name, e.g. `expired`. Ticks
+    /// already go out as `tick`.
+    pub fn publish(&self, event: &TimerEvent) {
+        if matches!(event, TimerEvent::Tick { .. }) {
+            return;
+        }
+        let data = serde_json::to_value(event).unwrap();
+        let name = data["event"].as_str().unwrap_or_default();
+        self.broadcast(http::event_frame(name, &data.to_string()));
+    }
+
+    fn broadcast(&self, frame: String) {
+        // Subscribers whose connection went away are dropped on the way.
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|subscriber| subscriber.send(frame.clone()).is_ok());
+    }
+
+    fn subscribe(&self) -> Receiver<String> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.lock().unwrap().push(sender);
+        receiver
+    }
+}
+
+/// Answer to a command: the state after it, and whether it changed anything
+#[derive(Serialize)]
+struct CommandReply {
+    outcome: Outcome,
+    #[serde(flatten)]
+    state: TimerState,
+}
+
+fn is_tick(previous: &TimerState, next: &TimerState) -> bool {
+    let advanced = TimerState {
+        remaining: previous.remaining - 1,
+        ..previous.clone()
+    };
+    advanced == *next
+}
+
+#[derive(Debug, PartialEq)]
+enum Route {
+    State,
+    Events,
+    Command(TimerCommand),
+}
+
+#[derive(Debug, PartialEq)]
+struct RouteError {
+    status: u16,
+    message: String,
+}
+
+fn route_error(status: u16, message: impl Into<String>) -> RouteError {
+    RouteError {
+        status,
+        message: message.into(),
+    }
+}
+
+fn route(request: &Request) -> Result<Route, RouteError> {
+    let command = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/state") => return Ok(Route::State),
+        ("GET", "/events") => return Ok(Route::Events),
+        ("POST", "/start") => TimerCommand::Start,
+        ("POST", "/pause") => TimerCommand::Pause,
+        ("POST", "/resume") => TimerCommand::Resume,
+        ("POST", "/reset") => TimerCommand::Reset,
+        ("POST", "/undo") => TimerCommand::Undo,
+        ("POST", "/redo") => TimerCommand::Redo,
+        ("POST", "/duration") => TimerCommand::SetDuration {
+            seconds: parse_seconds(&request.body)?,
+        },
+        ("POST", "/snooze") => TimerCommand::Snooze {
+            seconds: if request.body.is_empty() {
+                None
+            } else {
+                Some(parse_seconds(&request.body)?)
+            },
+        },
+        ("POST", "/command") => parse_command(&request.body)?,
+        (
+            _,
+            "/state" | "/events" | "/start" | "/pause" | "/resume" | "/reset" | "/undo" | "/redo"
+            | "/duration" | "/snooze" | "/command",
+        ) => return Err(route_error(405, "method not allowed")),
+        _ => return Err(route_error(404, "no such endpoint")),
+    };
+    dispatch::validate(&command).map_err(|err| route_error(400, err.to_string()))?;
+    Ok(Route::Command(command))
+}
+
+/// Parse `{"seconds": n}`
+fn parse_seconds(body: &[u8]) -> Result<i32, RouteError> {
+    #[derive(Deserialize)]
+    struct Body {
+        seconds: i32,
+    }
+
+    serde_json::from_slice::<Body>(body)
+        .map(|body| body.seconds)
+        .map_err(|_| route_error(400, "expected a JSON body like {\"seconds\": 300}"))
+}
+
+/// Parse a command as JSON, e.g. `{"command": "snooze", "seconds": 60}`
+fn parse_command(body: &[u8]) -> Result<TimerCommand, RouteError> {
+    let command = serde_json::from_slice(body)
+        .map_err(|_| route_error(400, "expected a JSON body like {\"command\": \"pause\"}"))?;
+    match command {
+        TimerCommand::Attach { .. } => Err(route_error(400, "only windows attach")),
+        command => Ok(command),
+    }
+}
+
+fn is_authorized(request: &Request, token: Option<&str>) -> bool {
+    match token {
+        None => true,
+        Some(token) => request
+            .header("authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|given| same_token(given, token)),
+    }
+}
+
+/// Compare every byte whatever the first difference, so how long a wrong
+/// guess takes says nothing about how much of it was right
+fn same_token(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+fn handle_connection(app: &AppHandle, mut stream: impl Read + Write, token: Option<&str>) {
+    let Ok(request) = http::read_request(&mut stream) else {
+        let _ = http::write_json(
+            stream,
+            400,
+            &serde_json::json!({"error": "malformed request"}),
+        );
+        return;
+    };
+    if !is_authorized(&request, token) {
+        let _ = http::write_json(
+            stream,
+            401,
+            &serde_json::json!({"error": "missing or wrong token"}),
+        );
+        return;
+    }
+
+    let timer = app.state::<TimerRuntime>();
+    let result = match route(&request) {
+        Ok(Route::State) => {
+            let state = TimerState::from(&timer.snapshot());
+            http::write_json(stream, 200, &serde_json::json!(state))
+        }
+        Ok(Route::Events) => stream_events(&app.state::<ControlHub>(), &timer, stream),
+        Ok(Route::Command(command)) => match timer.send(command) {
+            Ok(dispatched) => {
+                let reply = CommandReply {
+                    outcome: dispatched.outcome,
+                    state: TimerState::from(&dispatched.state),
+                };
+                http::write_json(stream, 200, &serde_json::json!(reply))
+            }
+            Err(err) => {
+                http::write_json(stream, 400, &serde_json::json!({"error": err.to_string()}))
+            }
+        },
+        Err(error) => http::write_json(
+            stream,
+            error.status,
+            &serde_json::json!({"error": error.message}),
+        ),
+    };
+    // Clients hanging up early is their business.
+    let _ = result;
+}
+
+/// Serve server-sent events until the client disconnects, starting with the
+/// current state so clients need not race a separate `GET /state`
+fn stream_events(hub: &ControlHub, timer: &TimerRuntime, mut stream: impl Write) -> io::Result<()> {
+    let frames = hub.subscribe();
+    http::write_event_stream_head(&mut stream)?;
+    let state = TimerState::from(&timer.snapshot());
+    let frame = http::event_frame("state", &serde_json::to_string(&state).unwrap());
+    stream.write_all(frame.as_bytes())?;
+    stream.flush()?;
+    forward(&frames, stream, KEEP_ALIVE)
+}
+
+/// Write each frame as it comes, or a keep-alive after `keep_alive` without
+/// one, until writing fails or the hub is gone
+fn forward(
+    frames: &Receiver<String>,
+    mut stream: impl Write,
+    keep_alive: Duration,
+) -> io::Result<()> {
+    loop {
+        let frame = match frames.recv_timeout(keep_alive) {
+            Ok(frame) => frame,
+            Err(RecvTimeoutError::Timeout) => http::KEEP_ALIVE_FRAME.to_string(),
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        };
+        stream.write_all(frame.as_bytes())?;
+        stream.flush()?;
+    }
+}
+
+/// Start the control API listeners. Each connection gets its own thread;
+/// event streams hold theirs for as long as the client stays.
+pub fn start(app: &AppHandle) -> io::Result<()> {
+    let dir = app.state::<DataDirs>().control.clone();
+    create_private_dir(&dir)?;
+
+    #[cfg(unix)]
+    serve_unix(app.clone(), &dir.join(SOCKET_FILE))?;
+
+    if let Ok(port) = std::env::var(PORT_ENV) {
+        let port: u16 = port.parse().map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{PORT_ENV} is not a port"),
+            )
+        })?;
+        let token = generate_token()?;
+        write_private(&dir.join(TOKEN_FILE), token.as_bytes())?;
+        serve_tcp(app.clone(), port, token)?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn serve_unix(app: AppHandle, path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    // A socket left over from a crash is removed; a live one means another
+    // instance is already serving.
+    if UnixStream::connect(path).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("{} is in use", path.display()),
+        ));
+    }
+    let _ = fs::remove_file(path);
+    // The folder is the owner's alone, so no one else can connect in the
+    // moment before the socket itself is restricted
+    let listener = UnixListener::bind(path)?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let app = app.clone();
+            let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+            thread::spawn(move || handle_connection(&app, stream, None));
+        }
+    });
+    Ok(())
+}
+
+fn serve_tcp(app: AppHandle, port: u16, token: String) -> io::Result<()> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let app = app.clone();
+            let token = token.clone();
+            let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+            thread::spawn(move || handle_connection(&app, stream, Some(&token)));
+        }
+    });
+    Ok(())
+}
+
+/// 128 bits from the OS's secure random source, as hex
+fn generate_token() -> io::Result<String> {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).map_err(|err| io::Error::other(err.to_string()))?;
+    Ok(bytes.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// Create `dir` if need be, readable by the owner only either way
+fn create_private_dir(dir: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)?;
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))
+    }
+    #[cfg(not(unix))]
+    {
+        fs::create_dir_all(dir)
+    }
+}
+
+fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, path: &str, body: &str) -> Request {
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            headers: Vec::new(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    fn state(remaining: i32) -> TimerSnapshot {
+        TimerSnapshot {
+            remaining,
+            ..TimerSnapshot::new(1500)
+        }
+    }
+
+    #[test]
+    fn test_route_commands() {
+        assert_eq!(route(&request("GET", "/state", "")), Ok(Route::State));
+        assert_eq!(route(&request("GET", "/events", "")), Ok(Route::Events));
+        assert_eq!(
+            route(&request("POST", "/pause", "")),
+            Ok(Route::Command(TimerCommand::Pause))
+        );
+        assert_eq!(
+            route(&request("POST", "/undo", "")),
+            Ok(Route::Command(TimerCommand::Undo))
+        );
+        assert_eq!(
+            route(&request("POST", "/duration", r#"{"seconds": 300}"#)),
+            Ok(Route::Command(TimerCommand::SetDuration { seconds: 300 }))
+        );
+        assert_eq!(
+            route(&request("POST", "/snooze", "")),
+            Ok(Route::Command(TimerCommand::Snooze { seconds: None }))
+        );
+        assert_eq!(
+            route(&request(
+                "POST",
+                "/command",
+                r#"{"command": "snooze", "seconds": 60}"#
+            )),
+            Ok(Route::Command(TimerCommand::Snooze { seconds: Some(60) }))
+        );
+        assert_eq!(
+            route(&request(
+                "POST",
+                "/command",
+                r#"{"command": "toggle_pause"}"#
+            )),
+            Ok(Route::Command(TimerCommand::TogglePause))
+        );
+    }
+
+    #[test]
+    fn test_route_errors() {
+        assert_eq!(
+            route(&request("GET", "/pause", "")).unwrap_err().status,
+            405
+        );
+        assert_eq!(route(&request("GET", "/nope", "")).unwrap_err().status, 404);
+        assert_eq!(
+            route(&request("POST", "/duration", "300"))
+                .unwrap_err()
+                .status,
+            400
+        );
+        assert_eq!(
+            route(&request("POST", "/duration", r#"{"seconds": 3600}"#))
+                .unwrap_err()
+                .status,
+            400
+        );
+        assert_eq!(
+            route(&request("POST", "/duration", r#"{"seconds": -1}"#))
+                .unwrap_err()
+                .status,
+            400
+        );
+        for body in [
+            r#"{"command": "explode"}"#,
+            r#"{"command": "attach", "saved_seconds": 60}"#,
+            r#"{"command": "snooze", "seconds": 0}"#,
+        ] {
+            assert_eq!(
+                route(&request("POST", "/command", body))
+                    .unwrap_err()
+                    .status,
+                400
+            );
+        }
+    }
+
+    #[test]
+    fn test_authorization() {
+        let mut req = request("GET", "/state", "");
+        assert!(is_authorized(&req, None));
+        assert!(!is_authorized(&req, Some("secret")));
+        req.headers
+            .push(("Authorization".to_string(), "Bearer secret".to_string()));
+        assert!(is_authorized(&req, Some("secret")));
+        assert!(!is_authorized(&req, Some("other")));
+        assert!(!is_authorized(&req, Some("secrets")));
+        assert!(!is_authorized(&req, Some("secreT")));
+    }
+
+    #[test]
+    fn test_events_classified_as_tick_or_transition() {
+        let hub = ControlHub::default();
+        let events = hub.subscribe();
+
+        hub.update_state(&state(10));
+        hub.update_state(&state(9));
+        // Blink phase changes are not broadcast
+        hub.update_state(&TimerSnapshot {
+            blink_visible: false,
+            ..state(9)
+        });
+        hub.update_state(&TimerSnapshot {
+            paused: true,
+            ..state(9)
+        });
+        // A reset jumps rather than ticks
+        hub.update_state(&state(1500));
+
+        let names: Vec<String> = events
+            .try_iter()
+            .map(|frame| frame.lines().next().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "event: transition",
+                "event: tick",
+                "event: transition",
+                "event: transition"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_timer_events_published_by_name() {
+        let hub = ControlHub::default();
+        let events = hub.subscribe();
+
+        hub.publish(&TimerEvent::Tick { remaining: 9 });
+        hub.publish(&TimerEvent::Paused { remaining: 9 });
+        hub.publish(&TimerEvent::Expired);
+
+        let frames: Vec<String> = events.try_iter().collect();
+        assert_eq!(
+            frames,
+            [
+                "event: paused\ndata: {\"event\":\"paused\",\"remaining\":9}\n\n",
+                "event: expired\ndata: {\"event\":\"expired\"}\n\n",
+            ]
+        );
+    }
+
+    /// A client that hung up: every write fails
+    struct HungUp;
+
+    impl Write for HungUp {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_idle_stream_notices_client_gone() {
+        let hub = ControlHub::default();
+        let frames = hub.subscribe();
+        // Nothing happens, yet the keep-alive finds the client gone
+        let result = forward(&frames, HungUp, Duration::from_millis(1));
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::BrokenPipe);
+
+        let frames = hub.subscribe();
+        hub.publish(&TimerEvent::Expired);
+        drop(hub);
+        let mut written = Vec::new();
+        forward(&frames, &mut written, Duration::from_secs(60)).unwrap();
+        assert_eq!(
+            String::from_utf8(written).unwrap(),
+            "event: expired\ndata: {\"event\":\"expired\"}\n\n"
+        );
+    }
+
+    #[test]
+    fn test_token_is_128_bits_of_hex() {
+        let token = generate_token().unwrap();
+        assert_eq!(token.len(), 32);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(token, generate_token().unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_private_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("countdown-control-{}", std::process::id()));
+        let nested = dir.join("control");
+        create_private_dir(&nested).unwrap();
+        assert_eq!(
+            fs::metadata(&nested).unwrap().permissions().mode() & 0o777,
+            0o700
+        );
+        // Tightened when it already existed
+        fs::set_permissions(&nested, fs::Permissions::from_mode(0o755)).unwrap();
+        create_private_dir(&nested).unwrap();
+        assert_eq!(
+            fs::metadata(&nested).unwrap().permissions().mode() & 0o777,
+            0o700
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,111 @@
+//! The window side of escalating a long overrun: growing the window,
+//! moving it to the middle of its screen and asking for attention as each
+//! stage is reached, and putting it back once the timer is reset. Faster
+//! blinking is the timer's own; the sound is played by the timer window.
+
+use crate::window_state::{self, Rect};
+use countdown_core::timer_logic::entered_escalation_stage;
+use countdown_core::{EscalationStage, Settings, TimerSnapshot};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, UserAttentionType, WebviewWindow};
+
+/// Managed as Tauri state
+#[derive(Default)]
+pub struct Escalation {
+    last_remaining: Mutex<Option<i32>>,
+    /// Where the window was before the first stage that moved or grew it
+    original: Mutex<Option<Rect>>,
+}
+
+impl Escalation {
+    /// Whether the window is away from where the user put it, so its
+    /// placement is not worth remembering
+    pub fn active(&self) -> bool {
+        self.original.lock().unwrap().is_some()
+    }
+
+    /// The stage `snapshot` reaches, if it is the moment one is reached
+    fn entered(&self, snapshot: &TimerSnapshot, settings: &Settings) -> Option<EscalationStage> {
+        let previous = self
+            .last_remaining
+            .lock()
+            .unwrap()
+            .replace(snapshot.remaining);
+        entered_escalation_stage(&settings.escalation, previous?, snapshot.remaining).cloned()
+    }
+
+    pub fn update(&self, app: &AppHandle, snapshot: &TimerSnapshot, settings: &Settings) {
+        let Some(window) = app.get_webview_window("main") else {
+            return;
+        };
+        if !snapshot.blinking {
+            if let Some(original) = self.original.lock().unwrap().take() {
+                if let Err(err) = restore(&window, original) {
+                    eprintln!("cannot put the window back: {err}");
+                }
+            }
+        }
+        if let Some(stage) = self.entered(snapshot, settings) {
+            if let Err(err) = self.escalate(&window, &stage) {
+                eprintln!("cannot escalate: {err}");
+            }
+        }
+    }
+
+    fn escalate(&self, window: &WebviewWindow, stage: &EscalationStage) -> tauri::Result<()> {
+        if stage.grow != 1.0 || stage.center {
+            let mut original = self.original.lock().unwrap();
+            if original.is_none() {
+                *original = Some(window_state::placement(window)?);
+            }
+            if stage.grow != 1.0 {
+                let size = original.unwrap();
+                window.set_size(PhysicalSize::new(
+                    (size.width as f32 * stage.grow).round() as u32,
+                    (size.height as f32 * stage.grow).round() as u32,
+                ))?;
+            }
+        }
+        if stage.center {
+            window.center()?;
+        }
+        if stage.request_attention {
+            window.request_user_attention(Some(UserAttentionType::Critical))?;
+        }
+        Ok(())
+    }
+}
+
+fn restore(window: &WebviewWindow, original: Rect) -> tauri::Result<()> {
+    window.set_size(PhysicalSize::new(original.width, original.height))?;
+    window.set_position(PhysicalPosition::new(original.x, original.y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entered_once_per_stage_and_again_after_reset() {
+        let settings = Settings {
+            escalation: vec![EscalationStage {
+                after_seconds: 2,
+                ..EscalationStage::default()
+            }],
+            ..Settings::default()
+        };
+        let escalation = Escalation::default();
+        let after = |remaining| {
+            escalation
+                .entered(&TimerSnapshot::new(remaining), &settings)
+                .map(|stage| stage.after_seconds)
+        };
+        assert_eq!(after(-1), None);
+        assert_eq!(after(-2), Some(2));
+        assert_eq!(after(-3), None);
+        // Reset
+        assert_eq!(after(1500), None);
+        assert_eq!(after(-1), None);
+        assert_eq!(after(-2), Some(2));
+    }
+}
@@ -1,22 +1,107 @@
-// Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
-#[tauri::command]
-fn greet(name: &str) -> String {
-    format!("Hello, {}! You've been greeted from Rust!", name)
-}
+mod alerts;
+mod cli;
+mod click_through;
+mod commands;
+mod control;
+mod escalation;
+mod lock;
+mod portable;
+mod scheduler;
+mod settings;
+mod settings_window;
+mod state_store;
+mod timer_runtime;
+mod tray;
+mod window_state;
+
+use settings::SettingsStore;
+use tauri::{Emitter, Manager};
+use timer_runtime::TimerRuntime;
+
+/// Event carrying the new `TimerSnapshot` after every change
+const TIMER_STATE_EVENT: &str = "timer-state";
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Sending a command to the running timer is all this process does
+    if let Some(code) = cli::run(&std::env::args().collect::<Vec<_>>()) {
+        std::process::exit(code);
+    }
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .setup(|_app| {
+        .setup(|app| {
             // Hides the app from the Dock and cmd + tab. Must be set here, not via
             // Info.plist's `LSUIElement`: tao re-applies the activation policy in
             // `applicationDidFinishLaunching` and discards whatever the plist set.
             #[cfg(target_os = "macos")]
-            _app.set_activation_policy(tauri::ActivationPolicy::Accessory);
+            app.set_activation_policy(tauri::ActivationPolicy::Accessory);
+
+            // The usual per-user folders, or beside the app in portable mode
+            let dirs = portable::DataDirs::resolve()?;
+            let settings_path = dirs.config.join(settings::SETTINGS_FILE);
+            let status = settings::load_or_create(&settings_path);
+            app.manage(SettingsStore::new(status.clone()));
+
+            app.manage(control::ControlHub::default());
+            app.manage(click_through::ClickThrough::default());
+            app.manage(escalation::Escalation::default());
+            app.manage(state_store::StateStore::open(
+                dirs.local_data.join(state_store::STATE_DIR),
+            ));
+            app.manage(dirs);
+            let handle = app.handle().clone();
+            let events = app.handle().clone();
+            app.manage(TimerRuntime::spawn(
+                status.settings,
+                move |snapshot| {
+                    let _ = handle.emit(TIMER_STATE_EVENT, snapshot);
+                    handle.state::<control::ControlHub>().update_state(snapshot);
+                    handle
+                        .state::<state_store::StateStore>()
+                        .remember_duration(snapshot);
+                    let settings = handle.state::<SettingsStore>().status().settings;
+                    handle
+                        .state::<escalation::Escalation>()
+                        .update(&handle, snapshot, &settings);
+                },
+                move |event| {
+                    events.state::<control::ControlHub>().publish(event);
+                    let settings = events.state::<SettingsStore>().status().settings;
+                    alerts::on_event(&events, event, &settings);
+                    lock::on_event(&events, event, &settings);
+                },
+            ));
+            settings::watch(app.handle().clone());
+
+            // Without a tray the settings are still a shortcut away
+            if let Err(err) = tray::create(app) {
+                eprintln!("tray icon unavailable: {err}");
+            }
+
+            // The window starts hidden and is shown once back in place
+            if let Some(window) = app.get_webview_window("main") {
+                window_state::restore_and_track(window)?;
+            }
+            // Still locked from the last run
+            lock::apply(app.handle(), lock::locked(app.handle()))?;
+
+            // The timer works without the API, so failing to serve it is not fatal.
+            if let Err(err) = control::start(app.handle()) {
+                eprintln!("control API unavailable: {err}");
+            }
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![greet])
+        .invoke_handler(tauri::generate_handler![
+            commands::timer_dispatch,
+            commands::timer_state,
+            commands::get_settings,
+            commands::save_settings,
+            commands::open_settings,
+            commands::get_click_through,
+            commands::get_lock,
+            commands::set_lock,
+            commands::step_window_size,
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
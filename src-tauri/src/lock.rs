@@ -0,0 +1,40 @@
+//! Lock mode: the timer window ignores clicks, keys and dragging until it
+//! is unlocked by the chord or passphrase in `Settings::lock`. The window
+//! enforces it; the backend keeps it in the saved state, so a restart does
+//! not undo it.
+
+use crate::state_store::StateStore;
+use countdown_core::{Settings, TimerEvent};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Event carrying whether the timer is locked, whenever it changes
+const LOCK_EVENT: &str = "lock-changed";
+
+pub fn locked(app: &AppHandle) -> bool {
+    app.state::<StateStore>().locked()
+}
+
+/// Lock or unlock the timer window
+pub fn set(app: &AppHandle, locked: bool) -> tauri::Result<()> {
+    app.state::<StateStore>().remember_lock(locked);
+    apply(app, locked)?;
+    let _ = app.emit(LOCK_EVENT, locked);
+    Ok(())
+}
+
+/// Hold the window's size while locked; the window itself ignores the rest
+pub fn apply(app: &AppHandle, locked: bool) -> tauri::Result<()> {
+    match app.get_webview_window("main") {
+        Some(window) => window.set_resizable(!locked),
+        None => Ok(()),
+    }
+}
+
+/// Unlock as time runs out, when the settings ask for it
+pub fn on_event(app: &AppHandle, event: &TimerEvent, settings: &Settings) {
+    if *event == TimerEvent::Expired && settings.lock.release_on_expiry && locked(app) {
+        if let Err(err) = set(app, false) {
+            eprintln!("cannot unlock: {err}");
+        }
+    }
+}
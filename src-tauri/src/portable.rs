@@ -0,0 +1,143 @@
+//! Where settings and saved state live: the usual per-user folders, or in
+//! portable mode a folder next to the app, so they travel with it. Portable
+//! mode is on when a `portable` file sits beside the app or it is started
+//! with `--portable`.
+
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// File beside the app that turns portable mode on; its contents are ignored
+pub const MARKER_FILE: &str = "portable";
+/// Command-line flag that turns portable mode on
+pub const FLAG: &str = "--portable";
+/// Folder beside the app holding everything in portable mode
+pub const DATA_DIR: &str = "countdown-data";
+/// `identifier` in tauri.conf.json, which names the per-user folders
+const IDENTIFIER: &str = env!("COUNTDOWN_IDENTIFIER");
+
+/// Managed as Tauri state
+pub struct DataDirs {
+    /// Holds `settings.json`
+    pub config: PathBuf,
+    /// Holds the window placement and saved state
+    pub local_data: PathBuf,
+    /// Holds the control socket and token: `local_data`, but in portable
+    /// mode a folder of the user's own, see `control_dir`
+    pub control: PathBuf,
+}
+
+impl DataDirs {
+    /// Found as Tauri's `app_config_dir` and `app_local_data_dir` find
+    /// them, but without a running app, so `--command` finds the same
+    /// folders the timer it talks to uses
+    pub fn resolve() -> io::Result<Self> {
+        let flag = env::args().any(|arg| arg == FLAG);
+        let portable = env::current_exe()
+            .ok()
+            .and_then(|exe| app_folder(&exe, env::var_os("APPIMAGE").map(PathBuf::from)))
+            .and_then(|folder| Some((portable_dir(&folder, flag)?, folder)));
+        if let Some((dir, folder)) = portable {
+            let base = dirs::runtime_dir().unwrap_or_else(env::temp_dir);
+            return Ok(Self {
+                config: dir.clone(),
+                local_data: dir,
+                control: control_dir(&base, &folder),
+            });
+        }
+        let (Some(config), Some(local_data)) = (dirs::config_dir(), dirs::data_local_dir()) else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "no home folder"));
+        };
+        let local_data = local_data.join(IDENTIFIER);
+        Ok(Self {
+            config: config.join(IDENTIFIER),
+            control: local_data.clone(),
+            local_data,
+        })
+    }
+}
+
+/// The folder the user sees the app in: beside the AppImage file rather
+/// than inside its mount, and beside the `.app` bundle rather than inside it
+fn app_folder(exe: &Path, appimage: Option<PathBuf>) -> Option<PathBuf> {
+    let app = appimage
+        .or_else(|| {
+            exe.ancestors()
+                .find(|dir| dir.extension().is_some_and(|ext| ext == "app"))
+                .map(Path::to_path_buf)
+        })
+        .unwrap_or_else(|| exe.to_path_buf());
+    app.parent().map(Path::to_path_buf)
+}
+
+/// Where the control socket goes in portable mode. Not beside the app,
+/// where a FAT or exFAT stick cannot hold a socket and a deep path can be
+/// too long for one, but in `base`, the runtime or temp folder, named for
+/// `app_folder` so copies in different places each get their own.
+fn control_dir(base: &Path, app_folder: &Path) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    app_folder.hash(&mut hasher);
+    base.join(format!("countdown-{:016x}", hasher.finish()))
+}
+
+/// The data folder in `app_folder` if portable mode is on
+fn portable_dir(app_folder: &Path, flag: bool) -> Option<PathBuf> {
+    (flag || app_folder.join(MARKER_FILE).is_file()).then(|| app_folder.join(DATA_DIR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_app_folder() {
+        assert_eq!(
+            app_folder(Path::new("/opt/countdown/countdown"), None),
+            Some(PathBuf::from("/opt/countdown"))
+        );
+        assert_eq!(
+            app_folder(
+                Path::new("/Volumes/usb/countdown.app/Contents/MacOS/countdown"),
+                None
+            ),
+            Some(PathBuf::from("/Volumes/usb"))
+        );
+        assert_eq!(
+            app_folder(
+                Path::new("/tmp/.mount_countdXYZ/usr/bin/countdown"),
+                Some(PathBuf::from("/home/me/apps/countdown.AppImage"))
+            ),
+            Some(PathBuf::from("/home/me/apps"))
+        );
+    }
+
+    #[test]
+    fn test_control_dir_per_app_folder() {
+        let base = Path::new("/run/user/1000");
+        let usb = control_dir(base, Path::new("/media/usb/deeply/nested/countdown"));
+        assert_eq!(usb.parent(), Some(base));
+        assert_eq!(
+            usb,
+            control_dir(base, Path::new("/media/usb/deeply/nested/countdown"))
+        );
+        assert_ne!(usb, control_dir(base, Path::new("/media/other/countdown")));
+        // Short whatever the app folder, to fit a socket path
+        assert_eq!(usb.file_name().unwrap().len(), "countdown-".len() + 16);
+    }
+
+    #[test]
+    fn test_portable_by_marker_or_flag() {
+        let dir = env::temp_dir().join(format!("countdown-portable-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        assert_eq!(portable_dir(&dir, false), None);
+        assert_eq!(portable_dir(&dir, true), Some(dir.join(DATA_DIR)));
+
+        fs::write(dir.join(MARKER_FILE), "").unwrap();
+        assert_eq!(portable_dir(&dir, false), Some(dir.join(DATA_DIR)));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
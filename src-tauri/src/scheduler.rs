@@ -0,0 +1,83 @@
+use countdown_core::interval_queue::IntervalQueue;
+use countdown_core::timer_provider::{TimerHandle, TimerProvider};
+use std::time::{Duration, Instant};
+
+/// Real provider for the timer thread, which sleeps until `next_due` and
+/// then calls `run_due`. Intervals are due as `IntervalQueue` has them, so
+/// one that fell behind (a suspended laptop, say) runs once rather than
+/// replaying every tick it missed.
+#[derive(Default)]
+pub struct ThreadScheduler {
+    intervals: IntervalQueue<Instant, Duration>,
+}
+
+impl ThreadScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// When the earliest live interval is due, or `None` when none is set
+    pub fn next_due(&self) -> Option<Instant> {
+        self.intervals.next_due()
+    }
+
+    /// Run every callback due at or before `now`, earliest first. Callbacks
+    /// may set or cancel intervals, including their own.
+    pub fn run_due(&self, now: Instant) {
+        self.intervals.run_due(now, |_| ());
+    }
+}
+
+impl TimerProvider for ThreadScheduler {
+    fn set_interval(&self, ms: u32, callback: Box<dyn FnMut()>) -> Box<dyn TimerHandle> {
+        let period = Duration::from_millis(ms.max(1).into());
+        self.intervals
+            .set_interval(Instant::now(), period, callback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    #[test]
+    fn test_thread_scheduler_runs_due_intervals_in_order() {
+        let scheduler = ThreadScheduler::new();
+        let fired = Rc::new(RefCell::new(Vec::new()));
+        let start = Instant::now();
+        let fired_fast = fired.clone();
+        let _fast =
+            scheduler.set_interval(500, Box::new(move || fired_fast.borrow_mut().push(500)));
+        let fired_slow = fired.clone();
+        let _slow =
+            scheduler.set_interval(1000, Box::new(move || fired_slow.borrow_mut().push(1000)));
+
+        // Nothing is due yet
+        scheduler.run_due(start);
+        assert!(fired.borrow().is_empty());
+
+        // Woken late, each due interval runs once, earliest first
+        scheduler.run_due(start + Duration::from_millis(1200));
+        assert_eq!(&*fired.borrow(), &[500, 1000]);
+        let next = scheduler.next_due().unwrap();
+        assert!(next > start + Duration::from_millis(1200));
+        assert!(next <= Instant::now() + Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn test_thread_scheduler_cancel() {
+        let scheduler = ThreadScheduler::new();
+        let fired = Rc::new(Cell::new(0));
+        let fired_clone = fired.clone();
+        let mut handle =
+            scheduler.set_interval(10, Box::new(move || fired_clone.set(fired_clone.get() + 1)));
+        assert!(scheduler.next_due().is_some());
+
+        handle.cancel();
+        scheduler.run_due(Instant::now() + Duration::from_secs(1));
+        assert_eq!(fired.get(), 0);
+        assert!(scheduler.next_due().is_none());
+    }
+}
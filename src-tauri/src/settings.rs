@@ -0,0 +1,269 @@
+//! The user's `settings.json`, in the app's config directory. It is created
+//! with the defaults on first run and reloaded whenever it changes, so edits
+//! take effect without a restart. A file that fails to parse or validate
+//! leaves the previous settings in force and the error on show.
+
+use crate::timer_runtime::TimerRuntime;
+use countdown_core::Settings;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager};
+
+pub const SETTINGS_FILE: &str = "settings.json";
+
+/// Event carrying the new `SettingsStatus` whenever the settings change
+const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+
+/// How often the file's modification time is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The settings in force, where they came from, and why the file was last
+/// rejected, if it was
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SettingsStatus {
+    pub settings: Settings,
+    pub path: PathBuf,
+    pub error: Option<String>,
+}
+
+/// Managed as Tauri state for `get_settings` and `save_settings`
+pub struct SettingsStore {
+    status: Mutex<SettingsStatus>,
+}
+
+impl SettingsStore {
+    pub fn new(status: SettingsStatus) -> Self {
+        Self {
+            status: Mutex::new(status),
+        }
+    }
+
+    pub fn status(&self) -> SettingsStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Take the result of reading the file, keeping the settings in force
+    /// if it failed. Returns the new status unless nothing changed.
+    fn update(&self, loaded: Result<Settings, String>) -> Option<SettingsStatus> {
+        let mut status = self.status.lock().unwrap();
+        let next = match loaded {
+            Ok(settings) => SettingsStatus {
+                settings,
+                error: None,
+                ..status.clone()
+            },
+            Err(err) => {
+                eprintln!("{err}");
+                SettingsStatus {
+                    error: Some(err),
+                    ..status.clone()
+                }
+            }
+        };
+        if next == *status {
+            return None;
+        }
+        *status = next.clone();
+        Some(next)
+    }
+
+    /// Validate and write `settings` to the file, replacing any error
+    fn save(&self, settings: Settings) -> Result<SettingsStatus, String> {
+        settings.validate().map_err(|err| err.to_string())?;
+        let mut status = self.status.lock().unwrap();
+        write(&status.path, &settings)
+            .map_err(|err| format!("cannot write {}: {err}", status.path.display()))?;
+        status.settings = settings;
+        status.error = None;
+        Ok(status.clone())
+    }
+}
+
+/// Read and validate a settings file, naming the file and every problem in
+/// the error
+pub fn load(path: &Path) -> Result<Settings, String> {
+    let text =
+        fs::read_to_string(path).map_err(|err| format!("cannot read {}: {err}", path.display()))?;
+    let settings: Settings =
+        serde_json::from_str(&text).map_err(|err| format!("{}: {err}", path.display()))?;
+    settings
+        .validate()
+        .map_err(|err| format!("{}:\n{err}", path.display()))?;
+    Ok(settings)
+}
+
+/// Load the settings at `path`, first writing the defaults there if there is
+/// no file yet so users have something to edit
+pub fn load_or_create(path: &Path) -> SettingsStatus {
+    if !path.exists() {
+        if let Err(err) = write(path, &Settings::default()) {
+            eprintln!("cannot create {}: {err}", path.display());
+        }
+    }
+    let store = SettingsStore::new(SettingsStatus {
+        settings: Settings::default(),
+        path: path.to_path_buf(),
+        error: None,
+    });
+    store.update(load_if_present(path));
+    store.status()
+}
+
+/// No file is no error: the defaults apply
+fn load_if_present(path: &Path) -> Result<Settings, String> {
+    match load(path) {
+        Err(_) if !path.exists() => Ok(Settings::default()),
+        loaded => loaded,
+    }
+}
+
+fn write(path: &Path, settings: &Settings) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string_pretty(settings).map_err(std::io::Error::other)?;
+    fs::write(path, json + "\n")
+}
+
+/// Save settings from the settings window and apply them everywhere
+pub fn save(app: &AppHandle, settings: Settings) -> Result<SettingsStatus, String> {
+    let status = app.state::<SettingsStore>().save(settings)?;
+    publish(app, &status);
+    Ok(status)
+}
+
+/// Hand new settings to the timer and every window
+fn publish(app: &AppHandle, status: &SettingsStatus) {
+    app.state::<TimerRuntime>()
+        .apply_settings(status.settings.clone());
+    let _ = app.emit(SETTINGS_CHANGED_EVENT, status);
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Reload the file on a thread whenever its modification time changes,
+/// publishing the result if it changed anything
+pub fn watch(app: AppHandle) {
+    thread::Builder::new()
+        .name("settings".into())
+        .spawn(move || {
+            let path = app.state::<SettingsStore>().status().path;
+            let mut last_modified = modified(&path);
+            loop {
+                thread::sleep(POLL_INTERVAL);
+                let now_modified = modified(&path);
+                if now_modified == last_modified {
+                    continue;
+                }
+                last_modified = now_modified;
+
+                let loaded = load_if_present(&path);
+                if let Some(status) = app.state::<SettingsStore>().update(loaded) {
+                    publish(&app, &status);
+                }
+            }
+        })
+        .expect("failed to spawn settings thread");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("countdown-settings-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    #[test]
+    fn test_missing_file_is_created_with_defaults() {
+        let path = temp_path("created.json");
+        let _ = fs::remove_file(&path);
+
+        let status = load_or_create(&path);
+        assert_eq!(status.settings, Settings::default());
+        assert_eq!(status.error, None);
+        assert_eq!(load(&path), Ok(Settings::default()));
+    }
+
+    #[test]
+    fn test_invalid_file_keeps_previous_settings() {
+        let path = temp_path("invalid.json");
+        fs::write(&path, r#"{"default_seconds": 5000}"#).unwrap();
+
+        let previous = Settings {
+            default_seconds: 600,
+            ..Settings::default()
+        };
+        let store = SettingsStore::new(SettingsStatus {
+            settings: previous.clone(),
+            path: path.clone(),
+            error: None,
+        });
+        let status = store.update(load_if_present(&path)).unwrap();
+        assert_eq!(status.settings, previous);
+        let error = status.error.unwrap();
+        assert!(error.contains("invalid.json"));
+        assert!(error.contains("`default_seconds` must be between 0 and 3599"));
+
+        // The same problem again is no change
+        assert_eq!(store.update(load_if_present(&path)), None);
+    }
+
+    #[test]
+    fn test_save_writes_and_clears_error() {
+        let path = temp_path("saved.json");
+        let store = SettingsStore::new(SettingsStatus {
+            settings: Settings::default(),
+            path: path.clone(),
+            error: Some("broken".into()),
+        });
+        let settings = Settings {
+            default_seconds: 300,
+            ..Settings::default()
+        };
+        let status = store.save(settings.clone()).unwrap();
+        assert_eq!(status.settings, settings);
+        assert_eq!(status.error, None);
+        assert_eq!(load(&path), Ok(settings.clone()));
+
+        // Reading back what was just saved changes nothing
+        assert_eq!(store.update(load_if_present(&path)), None);
+    }
+
+    #[test]
+    fn test_save_rejects_invalid_settings() {
+        let path = temp_path("rejected.json");
+        let _ = fs::remove_file(&path);
+        let store = SettingsStore::new(SettingsStatus {
+            settings: Settings::default(),
+            path: path.clone(),
+            error: None,
+        });
+        let settings = Settings {
+            blink_interval_ms: 0,
+            ..Settings::default()
+        };
+        let error = store.save(settings).unwrap_err();
+        assert!(error.contains("`blink_interval_ms`"));
+        assert!(!path.exists());
+        assert_eq!(store.status().settings, Settings::default());
+    }
+
+    #[test]
+    fn test_syntax_error_names_the_file() {
+        let path = temp_path("syntax.json");
+        fs::write(&path, "{ default_seconds: 600 }").unwrap();
+
+        let error = load(&path).unwrap_err();
+        assert!(error.starts_with(&path.display().to_string()));
+        assert!(error.contains("line 1"));
+    }
+}
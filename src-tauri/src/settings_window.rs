@@ -0,0 +1,19 @@
+//! The settings window: the same frontend as the timer, routed by the
+//! `#settings` fragment. At most one is open; opening it again focuses it.
+
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+
+const LABEL: &str = "settings";
+
+pub fn open(app: &AppHandle) -> tauri::Result<()> {
+    if let Some(window) = app.get_webview_window(LABEL) {
+        window.unminimize()?;
+        return window.set_focus();
+    }
+    WebviewWindowBuilder::new(app, LABEL, WebviewUrl::App("index.html#settings".into()))
+        .title("Countdown settings")
+        .inner_size(320.0, 1210.0)
+        .resizable(false)
+        .build()?;
+    Ok(())
+}
@@ -0,0 +1,207 @@
+//! The backend's copy of the state remembered between runs, as files in the
+//! app's local data folder. It outlives the webview's LocalStorage, so the
+//! duration kept here wins over the window's.
+
+use countdown_core::storage::{self, Storage, StorageError, StoredState};
+use countdown_core::TimerSnapshot;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Folder, in the app's local data folder, holding the files
+pub const STATE_DIR: &str = "state";
+
+/// One `<key>.json` file per key in a folder
+pub struct FileStorage {
+    dir: PathBuf,
+}
+
+impl FileStorage {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+}
+
+fn io_error(path: &Path, err: std::io::Error) -> StorageError {
+    StorageError::new(format!("{}: {err}", path.display()))
+}
+
+impl Storage for FileStorage {
+    fn read(&self, key: &str) -> Result<Option<String>, StorageError> {
+        let path = self.path(key);
+        match fs::read_to_string(&path) {
+            Ok(text) => Ok(Some(text)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(io_error(&path, err)),
+        }
+    }
+
+    /// Written aside and renamed over, so a crash mid-write leaves the old
+    /// file rather than half a new one
+    fn write(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        let path = self.path(key);
+        let partial = path.with_extension("json.partial");
+        fs::create_dir_all(&self.dir)
+            .and_then(|()| fs::write(&partial, value))
+            .and_then(|()| fs::rename(&partial, &path))
+            .map_err(|err| io_error(&path, err))
+    }
+
+    fn remove(&self, key: &str) -> Result<(), StorageError> {
+        let path = self.path(key);
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(io_error(&path, err)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Managed as Tauri state
+pub struct StateStore {
+    storage: FileStorage,
+    state: Mutex<StoredState>,
+}
+
+impl StateStore {
+    /// Load what is in `dir`; unreadable files are kept aside and start
+    /// afresh, see `storage::load`
+    pub fn open(dir: PathBuf) -> Self {
+        let storage = FileStorage::new(dir);
+        let state = match storage::load(&storage) {
+            Ok(loaded) => {
+                if let Some(problem) = loaded.backed_up {
+                    eprintln!("saved state unreadable, kept as a backup: {problem}");
+                }
+                loaded.state
+            }
+            Err(err) => {
+                eprintln!("cannot read saved state: {err}");
+                StoredState::default()
+            }
+        };
+        Self {
+            storage,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// The most recently set duration, if one was saved
+    pub fn duration(&self) -> Option<i32> {
+        self.state.lock().unwrap().duration()
+    }
+
+    /// Remember the duration `snapshot` resets to, if it was set rather
+    /// than the default, which is left to apply as it is next run
+    pub fn remember_duration(&self, snapshot: &TimerSnapshot) {
+        if snapshot.duration_set {
+            self.change(|state| state.duration_seconds = Some(snapshot.reset_time));
+        }
+    }
+
+    /// Whether the timer was left locked
+    pub fn locked(&self) -> bool {
+        self.state.lock().unwrap().locked
+    }
+
+    pub fn remember_lock(&self, locked: bool) {
+        self.change(|state| state.locked = locked);
+    }
+
+    /// Apply `change`, writing only when it changed something
+    fn change(&self, change: impl FnOnce(&mut StoredState)) {
+        let mut state = self.state.lock().unwrap();
+        let before = state.clone();
+        change(&mut state);
+        if *state == before {
+            return;
+        }
+        if let Err(err) = storage::save(&self.storage, &state) {
+            eprintln!("cannot save state: {err}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use countdown_core::storage::{BACKUP_KEY, STATE_KEY};
+    use countdown_core::{ManualClockProvider, Settings, TimerCommand, TimerEngine};
+    use std::rc::Rc;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "countdown-state-test-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_state_survives_reopening() {
+        let dir = temp_dir("reopen");
+        let store = StateStore::open(dir.clone());
+        assert_eq!(store.duration(), None);
+        store.remember_duration(&TimerSnapshot {
+            duration_set: true,
+            ..TimerSnapshot::new(600)
+        });
+        store.remember_lock(true);
+        let reopened = StateStore::open(dir.clone());
+        assert_eq!(reopened.duration(), Some(600));
+        assert!(reopened.locked());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    /// A run of the timer at `default_seconds`, attached as the first
+    /// window would be, remembering each state in `store`
+    fn launch(store: &StateStore, default_seconds: i32) -> TimerEngine {
+        let settings = Settings {
+            default_seconds,
+            ..Settings::default()
+        };
+        let engine = TimerEngine::new(Rc::new(ManualClockProvider::new()), settings);
+        engine.apply(TimerCommand::Attach {
+            saved_seconds: store.duration(),
+        });
+        store.remember_duration(&engine.snapshot());
+        engine
+    }
+
+    #[test]
+    fn test_changed_default_applies_next_run() {
+        let dir = temp_dir("default");
+        launch(&StateStore::open(dir.clone()), 1500);
+
+        // The default changed while the app was closed
+        let store = StateStore::open(dir.clone());
+        let engine = launch(&store, 600);
+        assert_eq!(engine.snapshot().reset_time, 600);
+
+        // A duration that was set outlasts the default
+        engine.apply(TimerCommand::SetDuration { seconds: 300 });
+        store.remember_duration(&engine.snapshot());
+        let engine = launch(&StateStore::open(dir.clone()), 900);
+        assert_eq!(engine.snapshot().reset_time, 300);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_corrupt_file_is_kept_aside() {
+        let dir = temp_dir("corrupt");
+        let storage = FileStorage::new(dir.clone());
+        storage.write(STATE_KEY, "{\"version\":").unwrap();
+        let store = StateStore::open(dir.clone());
+        assert_eq!(store.duration(), None);
+        assert_eq!(
+            storage.read(BACKUP_KEY).unwrap().as_deref(),
+            Some("{\"version\":")
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
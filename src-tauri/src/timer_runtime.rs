@@ -0,0 +1,105 @@
+//! The canonical timer. A dedicated thread owns the `TimerEngine` and wakes
+//! when the next interval is due or a command arrives, so the countdown keeps
+//! exact time however much the webview is throttled, and survives reloads.
+
+use crate::scheduler::ThreadScheduler;
+use countdown_core::dispatch::{self, CommandError, Dispatched};
+use countdown_core::{Settings, TimerCommand, TimerEngine, TimerEvent, TimerSnapshot};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+enum Request {
+    Command(TimerCommand, Sender<Result<Dispatched, CommandError>>),
+    // Boxed: settings dwarf every command
+    Settings(Box<Settings>),
+}
+
+/// Shared handle to the timer thread, managed as Tauri state
+pub struct TimerRuntime {
+    requests: Sender<Request>,
+    latest: Arc<Mutex<TimerSnapshot>>,
+}
+
+impl TimerRuntime {
+    /// Spawn the timer thread. `on_change` runs on it after every command,
+    /// settings change or batch of due intervals that changed the state, with
+    /// the new state; `on_event` runs just before, for each `TimerEvent` in
+    /// order. Neither may wait on the timer, e.g. by `send`.
+    pub fn spawn(
+        settings: Settings,
+        on_change: impl Fn(&TimerSnapshot) + Send + 'static,
+        on_event: impl Fn(&TimerEvent) + Send + 'static,
+    ) -> Self {
+        let (requests, receiver) = mpsc::channel::<Request>();
+        let latest = Arc::new(Mutex::new(TimerSnapshot::new(settings.default_seconds)));
+        let shared = latest.clone();
+
+        thread::Builder::new()
+            .name("timer".into())
+            .spawn(move || {
+                let scheduler = Rc::new(ThreadScheduler::new());
+                let engine = TimerEngine::new(scheduler.clone(), settings);
+                let events = Rc::new(RefCell::new(Vec::new()));
+                let heard = events.clone();
+                engine.subscribe(move |event| heard.borrow_mut().push(event.clone()));
+                let mut last = engine.snapshot();
+
+                loop {
+                    let message = match scheduler.next_due() {
+                        Some(due) => {
+                            receiver.recv_timeout(due.saturating_duration_since(Instant::now()))
+                        }
+                        None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                    };
+                    match message {
+                        Ok(Request::Command(command, reply)) => {
+                            let _ = reply.send(dispatch::dispatch(&engine, command));
+                        }
+                        Ok(Request::Settings(settings)) => engine.apply_settings(*settings),
+                        Err(RecvTimeoutError::Timeout) => scheduler.run_due(Instant::now()),
+                        Err(RecvTimeoutError::Disconnected) => return,
+                    }
+
+                    let snapshot = engine.snapshot();
+                    if snapshot != last {
+                        *shared.lock().unwrap() = snapshot.clone();
+                    }
+                    for event in events.take() {
+                        on_event(&event);
+                    }
+                    if snapshot != last {
+                        on_change(&snapshot);
+                        last = snapshot;
+                    }
+                }
+            })
+            .expect("failed to spawn timer thread");
+
+        Self { requests, latest }
+    }
+
+    /// Check and apply a command, as `dispatch::dispatch` does
+    pub fn send(&self, command: TimerCommand) -> Result<Dispatched, CommandError> {
+        let (reply, response) = mpsc::channel();
+        self.requests
+            .send(Request::Command(command, reply))
+            .expect("timer thread stopped");
+        response.recv().expect("timer thread stopped")
+    }
+
+    /// Switch the running timer to new settings
+    pub fn apply_settings(&self, settings: Settings) {
+        self.requests
+            .send(Request::Settings(Box::new(settings)))
+            .expect("timer thread stopped");
+    }
+
+    /// The current state, without waiting on the timer thread
+    pub fn snapshot(&self) -> TimerSnapshot {
+        self.latest.lock().unwrap().clone()
+    }
+}
@@ -0,0 +1,76 @@
+//! Tray icon, the way into the settings and out of click-through since the
+//! timer window has no menu, and a hand on the timer while it lets clicks
+//! through or is locked
+
+use crate::click_through::{self, ClickThrough};
+use crate::settings_window;
+use crate::timer_runtime::TimerRuntime;
+use countdown_core::dispatch;
+use tauri::menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::{App, Manager};
+
+/// Timer commands on the menu, by their name in `dispatch::COMMANDS`
+const TIMER_ITEMS: [(&str, &str); 3] = [
+    ("toggle_pause", "Pause / resume"),
+    ("reset", "Reset"),
+    ("snooze", "Snooze"),
+];
+
+pub fn create(app: &App) -> tauri::Result<()> {
+    let timer_items = TIMER_ITEMS
+        .iter()
+        .map(|(name, label)| MenuItem::with_id(app, *name, *label, true, None::<&str>))
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let settings = MenuItem::with_id(app, "settings", "Settings…", true, None::<&str>)?;
+    let click_through = CheckMenuItem::with_id(
+        app,
+        "click-through",
+        "Click-through",
+        true,
+        false,
+        None::<&str>,
+    )?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let mut items: Vec<&dyn IsMenuItem<_>> = timer_items
+        .iter()
+        .map(|item| item as &dyn IsMenuItem<_>)
+        .collect();
+    items.extend([
+        &separator as &dyn IsMenuItem<_>,
+        &settings,
+        &click_through,
+        &quit,
+    ]);
+    let menu = Menu::with_items(app, &items)?;
+    app.state::<ClickThrough>().attach_menu_item(click_through);
+
+    let mut tray = TrayIconBuilder::with_id("main")
+        .tooltip("countdown")
+        .menu(&menu)
+        .on_menu_event(|app, event| match event.id.as_ref() {
+            "settings" => {
+                if let Err(err) = settings_window::open(app) {
+                    eprintln!("cannot open settings: {err}");
+                }
+            }
+            "click-through" => {
+                if let Err(err) = click_through::toggle(app) {
+                    eprintln!("cannot toggle click-through: {err}");
+                }
+            }
+            "quit" => app.exit(0),
+            name => {
+                // A command the timer refuses changes nothing
+                if let Ok(command) = dispatch::parse(name, None) {
+                    let _ = app.state::<TimerRuntime>().send(command);
+                }
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        tray = tray.icon(icon.clone());
+    }
+    tray.build(app)?;
+    Ok(())
+}
@@ -0,0 +1,405 @@
+//! Where the timer window was left, remembered per monitor layout so a
+//! laptop on and off its dock each keep their own spot. On start the window
+//! goes back there, pulled onto a screen if that monitor is gone; once a
+//! drag settles near a screen edge or corner, it snaps flush to it.
+
+use crate::escalation::Escalation;
+use crate::portable::DataDirs;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+use tauri::{LogicalSize, Manager, PhysicalPosition, PhysicalSize, WebviewWindow, WindowEvent};
+
+pub const WINDOW_STATE_FILE: &str = "window-state.json";
+
+/// How long the window must stay put before a move counts as finished
+const SETTLE_TIME: Duration = Duration::from_millis(300);
+
+/// How close to an edge, in logical pixels, snaps the window to it
+const SNAP_DISTANCE: f64 = 24.0;
+
+/// The window's size as first configured, in logical pixels
+const BASE_SIZE: (f64, f64) = (100.0, 52.0);
+
+/// Sizes the window steps through from the keyboard, relative to `BASE_SIZE`
+const SIZE_PRESETS: [f64; 6] = [0.75, 1.0, 1.5, 2.0, 3.0, 4.0];
+
+/// A rectangle in physical pixels
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    fn right(&self) -> i32 {
+        self.x + self.width as i32
+    }
+
+    fn bottom(&self) -> i32 {
+        self.y + self.height as i32
+    }
+
+    fn overlap_area(&self, other: &Rect) -> i64 {
+        let width = self.right().min(other.right()) - self.x.max(other.x);
+        let height = self.bottom().min(other.bottom()) - self.y.max(other.y);
+        if width <= 0 || height <= 0 {
+            0
+        } else {
+            i64::from(width) * i64::from(height)
+        }
+    }
+}
+
+/// A monitor as far as placement cares
+#[derive(Clone, Debug, PartialEq)]
+pub struct Screen {
+    pub name: String,
+    pub bounds: Rect,
+    /// The bounds less menu bars, docks and taskbars
+    pub work_area: Rect,
+    pub scale_factor: f64,
+}
+
+/// Identifies a set of monitors, whatever order they are listed in
+pub fn layout_key(screens: &[Screen]) -> String {
+    let mut parts: Vec<String> = screens
+        .iter()
+        .map(|s| {
+            let b = s.bounds;
+            format!("{}@{},{}+{}x{}", s.name, b.x, b.y, b.width, b.height)
+        })
+        .collect();
+    parts.sort();
+    parts.join("|")
+}
+
+/// The screen `window` mostly lies on, or the first if it is on none
+fn home_screen<'a>(window: &Rect, screens: &'a [Screen]) -> Option<&'a Screen> {
+    screens
+        .iter()
+        .max_by_key(|s| s.work_area.overlap_area(window))
+        .filter(|s| s.work_area.overlap_area(window) > 0)
+        .or_else(|| screens.first())
+}
+
+/// Move `window` entirely inside the work area of the screen it mostly lies
+/// on, or onto the first screen if it is off all of them
+pub fn clamp_to_screens(window: Rect, screens: &[Screen]) -> Rect {
+    let Some(screen) = home_screen(&window, screens) else {
+        return window;
+    };
+    let area = screen.work_area;
+    // A window larger than the area keeps its top left corner visible
+    let x = window.x.min(area.right() - window.width as i32).max(area.x);
+    let y = window
+        .y
+        .min(area.bottom() - window.height as i32)
+        .max(area.y);
+    Rect { x, y, ..window }
+}
+
+/// Pull `window` flush against any work-area edge it is near, so it snaps
+/// into a corner when near two
+pub fn snap(window: Rect, screens: &[Screen]) -> Rect {
+    let Some(screen) = home_screen(&window, screens) else {
+        return window;
+    };
+    let area = screen.work_area;
+    let distance = (SNAP_DISTANCE * screen.scale_factor).round() as i32;
+    let mut snapped = window;
+    if (window.x - area.x).abs() <= distance {
+        snapped.x = area.x;
+    } else if (area.right() - window.right()).abs() <= distance {
+        snapped.x = area.right() - window.width as i32;
+    }
+    if (window.y - area.y).abs() <= distance {
+        snapped.y = area.y;
+    } else if (area.bottom() - window.bottom()).abs() <= distance {
+        snapped.y = area.bottom() - window.height as i32;
+    }
+    snapped
+}
+
+/// The preset after `scale` going up or down, staying put at either end.
+/// A window resized by hand between presets goes to the neighbouring one.
+pub fn step_scale(scale: f64, larger: bool) -> f64 {
+    const EPSILON: f64 = 0.01;
+    if larger {
+        SIZE_PRESETS
+            .into_iter()
+            .find(|&preset| preset > scale + EPSILON)
+            .unwrap_or(SIZE_PRESETS[SIZE_PRESETS.len() - 1])
+    } else {
+        SIZE_PRESETS
+            .into_iter()
+            .rev()
+            .find(|&preset| preset < scale - EPSILON)
+            .unwrap_or(SIZE_PRESETS[0])
+    }
+}
+
+/// Saved placements by `layout_key`
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowState {
+    pub layouts: BTreeMap<String, Rect>,
+}
+
+impl WindowState {
+    /// The saved state, or none if the file is missing or unreadable: a
+    /// lost position is not worth failing over
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        fs::write(path, json)
+    }
+}
+
+fn screens(window: &WebviewWindow) -> tauri::Result<Vec<Screen>> {
+    let rect = |position: &PhysicalPosition<i32>, size: &PhysicalSize<u32>| Rect {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    };
+    Ok(window
+        .available_monitors()?
+        .iter()
+        .map(|monitor| Screen {
+            name: monitor.name().cloned().unwrap_or_default(),
+            bounds: rect(monitor.position(), monitor.size()),
+            work_area: rect(&monitor.work_area().position, &monitor.work_area().size),
+            scale_factor: monitor.scale_factor(),
+        })
+        .collect())
+}
+
+pub fn placement(window: &WebviewWindow) -> tauri::Result<Rect> {
+    let position = window.outer_position()?;
+    let size = window.outer_size()?;
+    Ok(Rect {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    })
+}
+
+fn restore(window: &WebviewWindow, path: &Path) -> tauri::Result<()> {
+    let screens = screens(window)?;
+    let Some(saved) = WindowState::load(path)
+        .layouts
+        .remove(&layout_key(&screens))
+    else {
+        return Ok(());
+    };
+    let rect = clamp_to_screens(saved, &screens);
+    window.set_size(PhysicalSize::new(rect.width, rect.height))?;
+    window.set_position(PhysicalPosition::new(rect.x, rect.y))
+}
+
+/// Step the window to the next larger or smaller preset size. It is kept
+/// on screen and remembered like any other resize.
+pub fn step_size(window: &WebviewWindow, larger: bool) -> tauri::Result<()> {
+    let width = window.inner_size()?.width as f64 / window.scale_factor()?;
+    let scale = step_scale(width / BASE_SIZE.0, larger);
+    window.set_size(LogicalSize::new(BASE_SIZE.0 * scale, BASE_SIZE.1 * scale))
+}
+
+/// Bring the window back on screen if it settled off it, snap it if it
+/// settled near an edge, and remember where it is
+fn settle(window: &WebviewWindow, path: &Path) -> tauri::Result<()> {
+    // Grown or centred for an overrun, and put back after
+    let escalated = window.app_handle().try_state::<Escalation>();
+    if escalated.is_some_and(|escalation| escalation.active()) {
+        return Ok(());
+    }
+    let screens = screens(window)?;
+    let rect = placement(window)?;
+    let snapped = snap(clamp_to_screens(rect, &screens), &screens);
+    if snapped != rect {
+        // Moving fires another settle, which saves
+        return window.set_position(PhysicalPosition::new(snapped.x, snapped.y));
+    }
+    let mut state = WindowState::load(path);
+    state.layouts.insert(layout_key(&screens), rect);
+    state.save(path).map_err(Into::into)
+}
+
+/// Put the window back where it was for this monitor layout, show it, and
+/// keep track of it from now on. The window starts hidden so it does not
+/// flash at the default position first.
+pub fn restore_and_track(window: WebviewWindow) -> tauri::Result<()> {
+    let path = window
+        .state::<DataDirs>()
+        .local_data
+        .join(WINDOW_STATE_FILE);
+    if let Err(err) = restore(&window, &path) {
+        eprintln!("cannot restore window position: {err}");
+    }
+    window.show()?;
+
+    let (moves, settled) = mpsc::channel();
+    window.on_window_event(move |event| {
+        if matches!(event, WindowEvent::Moved(_) | WindowEvent::Resized(_)) {
+            let _ = moves.send(());
+        }
+    });
+    thread::Builder::new()
+        .name("window-state".into())
+        .spawn(move || loop {
+            if settled.recv().is_err() {
+                return;
+            }
+            // Wait out the rest of the drag
+            loop {
+                match settled.recv_timeout(SETTLE_TIME) {
+                    Ok(()) => continue,
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+            if let Err(err) = settle(&window, &path) {
+                eprintln!("cannot save window position: {err}");
+            }
+        })
+        .expect("failed to spawn window-state thread");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn screen(name: &str, x: i32, width: u32, height: u32) -> Screen {
+        Screen {
+            name: name.into(),
+            bounds: rect(x, 0, width, height),
+            // A 25px menu bar along the top
+            work_area: rect(x, 25, width, height - 25),
+            scale_factor: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_layout_key_ignores_order() {
+        let a = screen("Built-in", 0, 1440, 900);
+        let b = screen("DELL", 1440, 2560, 1440);
+        assert_eq!(
+            layout_key(&[a.clone(), b.clone()]),
+            layout_key(&[b.clone(), a.clone()])
+        );
+        assert_ne!(layout_key(&[a.clone(), b]), layout_key(&[a]));
+    }
+
+    #[test]
+    fn test_clamp_pulls_window_off_a_missing_monitor() {
+        // Left on an external monitor that is no longer there
+        let screens = [screen("Built-in", 0, 1440, 900)];
+        let clamped = clamp_to_screens(rect(3000, 500, 100, 52), &screens);
+        assert_eq!(clamped, rect(1340, 500, 100, 52));
+    }
+
+    #[test]
+    fn test_clamp_keeps_window_below_menu_bar() {
+        let screens = [screen("Built-in", 0, 1440, 900)];
+        assert_eq!(
+            clamp_to_screens(rect(-40, 0, 100, 52), &screens),
+            rect(0, 25, 100, 52)
+        );
+        // Already inside: untouched
+        let inside = rect(600, 400, 100, 52);
+        assert_eq!(clamp_to_screens(inside, &screens), inside);
+    }
+
+    #[test]
+    fn test_clamp_uses_the_screen_it_mostly_lies_on() {
+        let screens = [
+            screen("Built-in", 0, 1440, 900),
+            screen("DELL", 1440, 2560, 1440),
+        ];
+        // Straddling the boundary, mostly on the right-hand monitor
+        let clamped = clamp_to_screens(rect(1420, 1000, 100, 52), &screens);
+        assert_eq!(clamped, rect(1440, 1000, 100, 52));
+    }
+
+    #[test]
+    fn test_snap_to_corner() {
+        let screens = [screen("Built-in", 0, 1440, 900)];
+        assert_eq!(
+            snap(rect(1330, 840, 100, 52), &screens),
+            rect(1340, 848, 100, 52)
+        );
+        assert_eq!(snap(rect(10, 40, 100, 52), &screens), rect(0, 25, 100, 52));
+    }
+
+    #[test]
+    fn test_snap_to_one_edge_only() {
+        let screens = [screen("Built-in", 0, 1440, 900)];
+        assert_eq!(
+            snap(rect(700, 30, 100, 52), &screens),
+            rect(700, 25, 100, 52)
+        );
+        let away = rect(700, 400, 100, 52);
+        assert_eq!(snap(away, &screens), away);
+    }
+
+    #[test]
+    fn test_snap_distance_scales_with_display() {
+        let mut retina = screen("Built-in", 0, 2880, 1800);
+        retina.scale_factor = 2.0;
+        // 40 physical pixels is within 24 logical ones at 2x
+        assert_eq!(snap(rect(40, 400, 200, 104), &[retina]).x, 0);
+    }
+
+    #[test]
+    fn test_step_scale() {
+        assert_eq!(step_scale(1.0, true), 1.5);
+        assert_eq!(step_scale(1.0, false), 0.75);
+        assert_eq!(step_scale(4.0, true), 4.0);
+        assert_eq!(step_scale(0.75, false), 0.75);
+        // Resized by hand to between presets
+        assert_eq!(step_scale(1.2, true), 1.5);
+        assert_eq!(step_scale(1.2, false), 1.0);
+        // Rounding from physical pixels does not skip a preset
+        assert_eq!(step_scale(1.999, true), 3.0);
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let dir = std::env::temp_dir().join(format!("countdown-window-{}", std::process::id()));
+        let path = dir.join(WINDOW_STATE_FILE);
+        let mut state = WindowState::default();
+        state.layouts.insert("a".into(), rect(1, 2, 3, 4));
+        state.save(&path).unwrap();
+        assert_eq!(WindowState::load(&path), state);
+
+        fs::write(&path, "not json").unwrap();
+        assert_eq!(WindowState::load(&path), WindowState::default());
+    }
+}
@@ -1,147 +1,286 @@
-use crate::event_ui::{create_key_handler, setup_input_mode_listener, setup_pause_resume_listener};
-use crate::storage::load_remaining_seconds;
-use crate::time_format::format_time;
-use crate::timer_provider::{GlooTimerProvider, TimerHandle, TimerProvider};
-use crate::timer_service::start_countdown_timer;
-use crate::view_components::{create_timer_display_view, create_timer_input_view};
-use std::cell::RefCell;
-use std::rc::Rc;
+use crate::click_through_client;
+use crate::event_ui::{
+    set_timeout, setup_input_mode_listener, setup_keymap_listener, setup_lock_guard,
+};
+use crate::lock_client;
+use crate::reduced_motion;
+use crate::settings_client::{self, SettingsStatus};
+use crate::sound::play_expiry_tone;
+use crate::storage::{load_remaining_seconds, save_remaining_seconds};
+use crate::timer_client;
+use crate::view_components::{
+    create_announcement_view, create_click_through_view, create_lock_view, create_progress_view,
+    create_settings_error_view, create_timer_display_view, create_timer_input_view,
+    create_undo_hint_view, DisplayState, ExpiryState,
+};
+use countdown_core::announce::announcement;
+use countdown_core::history::offers_undo;
+use countdown_core::style_utils::{
+    blink_visible, compute_container_style, compute_expiry_style, compute_fade_style,
+    compute_progress_style, is_revealed, progress_fraction,
+};
+use countdown_core::time_format::format_time;
+use countdown_core::timer_logic::{entered_escalation_stage, entered_warning_stage, warning_stage};
+use countdown_core::{ExpiryCues, TimerSnapshot};
 use sycamore::prelude::*;
 use web_sys::MouseEvent;
 
+/// How long the undo hint stays after a reset or new duration
+const UNDO_HINT_MS: i32 = 4000;
+
 #[component]
 pub fn App() -> View {
-    // instantiate timer provider as Rc<dyn TimerProvider> for sharing across closures
-    let timer_provider: Rc<dyn TimerProvider> = Rc::new(GlooTimerProvider);
-    // Clone provider for mount event to avoid moving original
-    let timer_provider_for_mount = timer_provider.clone();
-
-    // Load remaining time from LocalStorage or use default
-    let saved_remaining_seconds = load_remaining_seconds();
-
     // --- Countdown timer state ---
-    // Remaining time signal (seconds), using time loaded from LocalStorage
-    let remaining_time = create_signal(saved_remaining_seconds);
-
-    // Custom reset time signal, initialized with time loaded from LocalStorage
-    let reset_time = create_signal(Some(saved_remaining_seconds));
+    // The timer runs in the backend; this is its latest state, `None` until
+    // the first one arrives
+    let timer = create_signal(None::<TimerSnapshot>);
+    let field = move |f: fn(&TimerSnapshot) -> bool| {
+        create_memo(move || timer.with(|t| t.as_ref().is_some_and(f)))
+    };
+    let is_blinking_signal = field(|t| t.blinking);
+    let is_blink_visible_signal = field(|t| t.blink_visible);
+    let is_paused = field(|t| t.paused);
+    // true = entering time, false = countdown
+    let input_mode = field(|t| t.editing);
+    let is_attached = field(|_| true);
 
-    // Handle for the main countdown timer
-    let countdown_timer_handle: Rc<RefCell<Option<Box<dyn TimerHandle>>>> =
-        Rc::new(RefCell::new(None));
+    // --- Format display (MM:SS) ---
+    // Use create_memo to recompute only when the remaining time changes
+    let remaining_time = create_memo(move || timer.with(|t| t.as_ref().map(|t| t.remaining)));
+    let remaining = create_memo(move || remaining_time.get().unwrap_or(0));
+    let formatted_time = create_memo(move || format_time(remaining.get()));
 
-    // Blink state and timer handle after countdown ends
-    let is_blinking_signal = create_signal(false);
-    let is_blink_visible_signal = create_signal(true);
-    let blink_timer_handle: Rc<RefCell<Option<Box<dyn TimerHandle>>>> = Rc::new(RefCell::new(None));
+    // --- Settings ---
+    // From the backend, the defaults until they arrive; a problem with the
+    // file is shown as a badge
+    let settings_status = create_signal(None::<SettingsStatus>);
+    let settings = create_memo(move || {
+        settings_status
+            .with(|s| s.as_ref().map(|s| s.settings.clone()))
+            .unwrap_or_default()
+    });
+    let settings_error =
+        create_memo(move || settings_status.with(|s| s.as_ref().and_then(|s| s.error.clone())));
+    let theme = create_memo(move || settings.with(|s| s.active_theme()));
+    let progress = create_memo(move || settings.with(|s| s.progress));
+    let display = create_memo(move || settings.with(|s| s.display));
+    let reset = create_memo(move || settings.with(|s| s.reset.clone()));
 
-    // --- Format display (MM:SS) ---
-    // Use create_memo to recompute only when remaining_time changes
-    let formatted_time = create_memo(move || format_time(remaining_time.get()));
+    // --- Time up: blinking, or gentler if the system asks for less motion ---
+    let reduced_motion = create_signal(false);
+    let expiry_cues = create_memo(move || {
+        settings.with(|s| ExpiryCues {
+            motion: s.expiry.motion.resolve(reduced_motion.get()),
+            ..s.expiry.clone()
+        })
+    });
+    let expiry = ExpiryState {
+        blinking: is_blinking_signal,
+        visible: create_memo(move || {
+            blink_visible(
+                is_blink_visible_signal.get(),
+                expiry_cues.with(|c| c.motion),
+            )
+        }),
+        cues: expiry_cues,
+    };
 
-    // --- Pause state ---
-    // Pause state signal
-    let is_paused = create_signal(false);
+    // --- Warning as time runs low ---
+    let warning = create_memo(move || {
+        settings.with(|s| warning_stage(&s.warnings, remaining.get()).cloned())
+    });
 
-    // --- Input mode ---
-    // true = entering time, false = countdown
-    let input_mode = create_signal(false);
-    // user input string ("mm:ss"), formatted using time loaded from LocalStorage
-    let input_value = create_signal(format_time(saved_remaining_seconds));
+    // --- Progress through the set duration ---
+    let progress_style = create_memo(move || {
+        let fraction = timer.with(|t| {
+            t.as_ref()
+                .map_or(0.0, |t| progress_fraction(t.remaining, t.reset_time))
+        });
+        theme.with(|theme| {
+            compute_progress_style(
+                progress.get(),
+                fraction,
+                is_blinking_signal.get(),
+                expiry.visible.get(),
+                is_paused.get(),
+                warning.get_clone().as_ref(),
+                &theme.colors,
+            )
+        })
+    });
 
-    // --- Setup timer logic ---
-    // Use on_mount to start the timer when the component mounts
-    let countdown_handle_for_mount = countdown_timer_handle.clone();
-    let blink_handle_for_mount = blink_timer_handle.clone();
-    on_mount(move || {
-        // start countdown using shared timer provider clone by value
-        start_countdown_timer(
-            timer_provider_for_mount.clone(),
-            &countdown_handle_for_mount,
-            &remaining_time,
-            &blink_handle_for_mount,
-            &is_blinking_signal,
-            &is_blink_visible_signal,
-        );
-
-        // simplified pause/resume listener setup
-        setup_pause_resume_listener(
-            timer_provider_for_mount.clone(),
-            is_paused.clone(),
-            countdown_handle_for_mount.clone(),
-            blink_handle_for_mount.clone(),
-            remaining_time.clone(),
-            is_blinking_signal.clone(),
-            is_blink_visible_signal.clone(),
-        );
+    // --- Fading while there is plenty of time ---
+    let container_style = create_memo(move || {
+        settings.with(|s| {
+            let revealed = is_revealed(
+                &s.fade,
+                remaining.get(),
+                is_blinking_signal.get(),
+                is_paused.get(),
+            );
+            format!(
+                "{} {} {}",
+                theme.with(compute_container_style),
+                compute_fade_style(&s.fade, revealed),
+                expiry_cues.with(|cues| {
+                    theme.with(|theme| {
+                        compute_expiry_style(cues, is_blinking_signal.get(), &theme.colors)
+                    })
+                })
+            )
+        })
+    });
 
-        // Register input mode Enter/Escape listener
-        setup_input_mode_listener(
-            input_mode.clone(),
-            input_value.clone(),
-            remaining_time.clone(),
-            timer_provider_for_mount.clone(),
-            countdown_handle_for_mount.clone(),
-            blink_handle_for_mount.clone(),
-            is_blinking_signal.clone(),
-            is_blink_visible_signal.clone(),
-            is_paused.clone(),
-            reset_time.clone(),
-        );
+    // Sound the alert as time runs out, not when a window opens on a timer
+    // that is already blinking
+    let was_blinking = create_signal(None::<bool>);
+    create_effect(move || {
+        let blinking = timer.with(|t| t.as_ref().map(|t| t.blinking));
+        if blinking == Some(true)
+            && was_blinking.get_untracked() == Some(false)
+            && settings.with_untracked(|s| s.alerts.sound)
+        {
+            play_expiry_tone();
+        }
+        was_blinking.set(blinking);
+    });
+    // ...on counting down into a warning stage that asks for it, and on
+    // reaching an overrun escalation stage that does
+    let previous_remaining = create_signal(None::<i32>);
+    create_effect(move || {
+        let current = remaining_time.get();
+        if let (Some(previous), Some(current)) = (previous_remaining.get_untracked(), current) {
+            let alert = settings.with_untracked(|s| {
+                (s.alerts.sound
+                    && entered_warning_stage(&s.warnings, previous, current)
+                        .is_some_and(|stage| stage.alert))
+                    || entered_escalation_stage(&s.escalation, previous, current)
+                        .is_some_and(|stage| stage.sound)
+            });
+            if alert {
+                play_expiry_tone();
+            }
+        }
+        previous_remaining.set(current);
     });
 
-    // --- Cleanup timer ---
-    // Use on_cleanup to ensure timers are canceled on unmount to prevent memory leaks
-    let countdown_timer_handle_on_cleanup = countdown_timer_handle.clone();
-    let blink_timer_handle_on_cleanup = blink_timer_handle.clone();
-    on_cleanup(move || {
-        if let Some(handle) = countdown_timer_handle_on_cleanup.take() {
-            drop(handle); // Cancel main countdown Interval
+    // Faint and marked while clicks pass through the window
+    let click_through = create_signal(false);
+    // Marked, and neither clicked nor dragged, while locked
+    let locked = create_signal(false);
+    let lock = create_memo(move || settings.with(|s| s.lock.clone()));
+    let container_class = move || {
+        let mut class = String::from("timer-container");
+        if click_through.get() {
+            class.push_str(" click-through");
         }
-        if let Some(handle) = blink_timer_handle_on_cleanup.borrow_mut().take() {
-            drop(handle); // Cancel blink Interval
+        if locked.get() {
+            class.push_str(" locked");
         }
+        class
+    };
+
+    // Told to screen readers: changes of state and milestones. A reset or
+    // new duration also offers its undo for a moment.
+    let announced = create_signal(String::new());
+    let undo_hint = create_signal(0u32);
+    let undo_offered = create_memo(move || undo_hint.get() != 0 && !input_mode.get());
+    let previous_timer = create_signal(None::<TimerSnapshot>);
+    create_effect(move || {
+        let current = timer.get_clone();
+        if let (Some(previous), Some(current)) = (previous_timer.get_clone_untracked(), &current) {
+            if let Some(text) = announcement(&previous, current) {
+                announced.set(text);
+            }
+            if offers_undo(&previous, current) {
+                show_for(undo_hint, UNDO_HINT_MS);
+            }
+        }
+        previous_timer.set(current);
     });
 
-    // Create key event handler
-    let key_handler = create_key_handler(
-        input_mode.clone(),
-        input_value.clone(),
-        remaining_time.clone(),
-        countdown_timer_handle.clone(),
-        is_paused.clone(),
-        is_blinking_signal.clone(),
-    );
+    // user input string ("mm:ss"), filled in when input mode opens
+    let input_value = create_signal(String::new());
+
+    // Remember the set duration in LocalStorage, wherever it was set from;
+    // left unset, the default applies as it is next run
+    create_effect(move || {
+        let set = timer.with(|t| t.as_ref().filter(|t| t.duration_set).map(|t| t.reset_time));
+        if let Some(reset_time) = set {
+            save_remaining_seconds(reset_time);
+        }
+    });
+
+    // --- Setup timer logic ---
+    // Use on_mount to attach to the backend timer when the component mounts
+    on_mount(move || {
+        timer_client::connect(timer, load_remaining_seconds());
+        settings_client::connect(settings_status);
+        click_through_client::connect(click_through);
+        lock_client::connect(locked);
+        reduced_motion::connect(reduced_motion);
+
+        // Ahead of every other listener, whatever order they are added in
+        setup_lock_guard(settings, locked, input_mode);
+        // Every shortcut, as remapped in the settings
+        setup_keymap_listener(timer, input_value, settings);
+
+        // Register input mode Enter/Escape listener
+        setup_input_mode_listener(input_mode, input_value);
+    });
 
     view! {
         div(
             // Not in CSS: `-webkit-app-region` is a no-op in macOS' WKWebView.
-            data-tauri-drag-region="deep",
-            class="timer-container",
-            tabindex="0",
-            on:keydown=key_handler,
+            data-tauri-drag-region=move || if locked.get() { "false" } else { "deep" },
+            class=container_class,
+            role="timer",
+            aria-label="Countdown",
+            style=container_style,
             // Windows keeps its native window menu on the drag region, which is
             // non-client and so never reaches the webview.
             on:contextmenu=|ev: MouseEvent| ev.prevent_default(),
         ) {
-            (if input_mode.get() {
-                create_timer_input_view(input_value.clone(), input_mode.clone())
+            (if is_attached.get() {
+                create_progress_view(progress, progress_style)
+            } else {
+                view! {}
+            })
+            (if !is_attached.get() {
+                view! {}
+            } else if input_mode.get() {
+                create_timer_input_view(input_value, theme)
             } else {
                 create_timer_display_view(
-                    formatted_time.clone(),
-                    is_blinking_signal.clone(),
-                    is_blink_visible_signal.clone(),
-                    is_paused.clone(),
-                    timer_provider.clone(),
-                    countdown_timer_handle.clone(),
-                    blink_timer_handle.clone(),
-                    remaining_time.clone(),
-                    is_blinking_signal.clone(),
-                    is_blink_visible_signal.clone(),
-                    is_paused.clone(),
-                    reset_time.clone()
+                    DisplayState {
+                        formatted_time,
+                        remaining,
+                    },
+                    display,
+                    expiry,
+                    is_paused,
+                    warning,
+                    theme,
+                    reset,
                 )
             })
+            (create_undo_hint_view(undo_offered))
+            (create_announcement_view(*announced))
+            (create_click_through_view(*click_through))
+            (create_lock_view(*locked, lock))
+            (create_settings_error_view(settings_error))
         }
     }
 }
+
+/// Raise `shown` for `ms`, counting from the latest call if raised again
+/// meanwhile; zero is hidden
+fn show_for(shown: Signal<u32>, ms: i32) {
+    let generation = shown.get_untracked().wrapping_add(1).max(1);
+    shown.set(generation);
+    set_timeout(ms, move || {
+        if shown.get_untracked() == generation {
+            shown.set(0);
+        }
+    });
+}
@@ -0,0 +1,21 @@
+//! Whether the timer window lets clicks through to what is beneath it.
+//! Only the tray turns it on and off; the window just shows it.
+
+use crate::ipc;
+use sycamore::prelude::*;
+
+/// Event the backend emits whenever click-through is turned on or off
+const CLICK_THROUGH_EVENT: &str = "click-through-changed";
+
+#[derive(serde::Serialize)]
+struct NoArgs {}
+
+/// Follow the backend's click-through state in `enabled`
+pub fn connect(enabled: Signal<bool>) {
+    ipc::listen(CLICK_THROUGH_EVENT, move |new: bool| enabled.set(new));
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Some(current) = ipc::call::<_, bool>("get_click_through", &NoArgs {}).await {
+            enabled.set(current);
+        }
+    });
+}
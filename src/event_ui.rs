@@ -1,10 +1,13 @@
-use crate::event_logic::{format_time_input, handle_click, parse_time_input};
-use crate::storage::save_remaining_seconds;
-use crate::time_format::format_time;
-use crate::timer_provider::{TimerHandle, TimerProvider};
-use crate::timer_service::start_countdown_timer;
-use std::{cell::RefCell, rc::Rc};
-use sycamore::prelude::Signal;
+use crate::lock_client;
+use crate::settings_client;
+use crate::timer_client;
+use crate::window_client;
+use countdown_core::chord::{KeyPress, Modifiers};
+use countdown_core::event_logic::{format_time_input, parse_time_input};
+use countdown_core::keymap::{Action, Keymap};
+use countdown_core::time_format::format_time;
+use countdown_core::{Settings, TimerCommand, TimerSnapshot};
+use sycamore::prelude::*;
 use wasm_bindgen::closure::Closure;
 use wasm_bindgen::JsCast;
 use web_sys::{HtmlInputElement, KeyboardEvent};
@@ -23,141 +26,158 @@ pub fn handle_timer_input(event: web_sys::Event, input_value: Signal<String>) {
     }
 }
 
-/// Helper function to create key event handler for F key
-pub fn create_key_handler(
-    input_mode: Signal<bool>,
-    input_value: Signal<String>,
-    remaining_time: Signal<i32>,
-    countdown_handle: Rc<RefCell<Option<Box<dyn TimerHandle>>>>,
-    paused_signal: Signal<bool>,
-    blinking_signal: Signal<bool>,
-) -> impl Fn(KeyboardEvent) + 'static {
-    move |ev: KeyboardEvent| {
-        handle_toggle_input_mode(
-            ev,
-            input_mode.clone(),
-            input_value.clone(),
-            remaining_time.clone(),
-            countdown_handle.clone(),
-            paused_signal.clone(),
-            blinking_signal.clone(),
-        );
+/// The key press `event` describes, for matching against chords
+pub fn key_press(event: &KeyboardEvent) -> KeyPress {
+    KeyPress {
+        key: event.key(),
+        code: event.code(),
+        held: Modifiers {
+            ctrl: event.ctrl_key(),
+            alt: event.alt_key(),
+            shift: event.shift_key(),
+            meta: event.meta_key(),
+        },
     }
 }
 
-/// Handle toggle input mode via 'f' key. Leaving input mode is Enter or
-/// Escape only, handled by `setup_input_mode_listener`; while in input mode
-/// 'f' is ordinary typing that `format_time_input` discards.
-pub fn handle_toggle_input_mode(
-    event: KeyboardEvent,
-    input_mode: Signal<bool>,
+/// Run the action each key is bound to in the settings' keymap. While a
+/// time is being entered only the settings open; Enter and Escape belong to
+/// `setup_input_mode_listener` then. Reset, like the click, is ignored
+/// until time is up if the settings say.
+pub fn setup_keymap_listener(
+    timer: Signal<Option<TimerSnapshot>>,
     input_value: Signal<String>,
-    remaining_time: Signal<i32>,
-    countdown_handle: Rc<RefCell<Option<Box<dyn TimerHandle>>>>,
-    paused_signal: Signal<bool>,
-    blinking_signal: Signal<bool>,
-) -> bool {
-    if input_mode.get() || event.key() != "f" {
-        return false;
-    }
-
-    event.prevent_default();
-    let time_str = format_time(remaining_time.get());
-    input_value.set(time_str);
-    input_mode.set(true);
-
-    // Pause the timer when entering input mode
-    if !paused_signal.get() && !blinking_signal.get() {
-        paused_signal.set(true);
-        if let Some(mut h) = countdown_handle.borrow_mut().take() {
-            h.cancel();
+    settings: ReadSignal<Settings>,
+) {
+    let window = web_sys::window().unwrap();
+    let closure = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+        // Keys mid-composition are the input method's
+        if event.is_composing() {
+            return;
         }
-    }
-
-    focus_timer_input();
-    true
+        let Some(state) = timer.get_clone() else {
+            return;
+        };
+        let press = key_press(&event);
+        let Some(action) = settings.with(|s| Keymap::new(s).action(&press)) else {
+            return;
+        };
+        if state.editing && action != Action::OpenSettings {
+            return;
+        }
+        match action {
+            Action::TogglePause => timer_client::dispatch(TimerCommand::TogglePause),
+            Action::EnterEdit => enter_edit(timer, input_value, state),
+            Action::CycleDisplay => cycle_display(settings),
+            Action::Grow => window_client::step_size(true),
+            Action::Shrink => window_client::step_size(false),
+            Action::Reset if settings.with(|s| s.reset.allows(state.blinking)) => {
+                timer_client::dispatch(TimerCommand::Reset)
+            }
+            Action::Reset => return,
+            Action::Snooze => timer_client::dispatch(TimerCommand::Snooze { seconds: None }),
+            Action::Undo => timer_client::dispatch(TimerCommand::Undo),
+            Action::Redo => timer_client::dispatch(TimerCommand::Redo),
+            Action::OpenSettings => settings_client::open(),
+            // Taken by `setup_lock_guard` before this sees it
+            Action::Lock => return,
+        }
+        event.prevent_default();
+    }) as Box<dyn FnMut(_)>);
+    window
+        .add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())
+        .unwrap();
+    closure.forget();
 }
 
-/// Handle click event and reset the timer
-pub fn handle_click_and_reset(
-    provider: Rc<dyn TimerProvider>,
-    countdown_handle: Rc<RefCell<Option<Box<dyn TimerHandle>>>>,
-    blink_handle: Rc<RefCell<Option<Box<dyn TimerHandle>>>>,
-    remaining_time: Signal<i32>,
-    blinking_signal: Signal<bool>,
-    visible_signal: Signal<bool>,
-    reset_time: Signal<Option<i32>>,
+/// Open time entry on the current time. Leaving it is Enter or Escape only,
+/// handled by `setup_input_mode_listener`.
+fn enter_edit(
+    timer: Signal<Option<TimerSnapshot>>,
+    input_value: Signal<String>,
+    state: TimerSnapshot,
 ) {
-    let result = handle_click(
-        remaining_time.get(),
-        blinking_signal.get(),
-        reset_time.get(),
-    );
-    remaining_time.set(result.reset_remaining);
-    if result.should_cancel_blink {
-        if let Some(mut handle) = blink_handle.borrow_mut().take() {
-            handle.cancel();
-        }
-    }
-    blinking_signal.set(result.is_blinking);
-    visible_signal.set(result.is_visible);
-    start_countdown_timer(
-        provider,
-        &countdown_handle,
-        &remaining_time,
-        &blink_handle,
-        &blinking_signal,
-        &visible_signal,
-    );
+    input_value.set(format_time(state.remaining));
+    // Show the input at once rather than after the round trip; the backend
+    // pauses the countdown and confirms with the next state event.
+    timer.set(Some(TimerSnapshot {
+        editing: true,
+        ..state
+    }));
+    timer_client::dispatch(TimerCommand::EnterEdit);
+}
+
+/// Switch to the next display, remembering the choice in the settings
+fn cycle_display(settings: ReadSignal<Settings>) {
+    let current = settings.get_clone();
+    let next = Settings {
+        display: current.display.next(),
+        ..current
+    };
+    wasm_bindgen_futures::spawn_local(async move {
+        // Every window follows the saved settings, this one included
+        let _ = settings_client::save(next).await;
+    });
 }
 
-/// Setup pause/resume key handler on window
-pub fn setup_pause_resume_listener(
-    timer_provider: Rc<dyn TimerProvider>,
-    paused_signal: Signal<bool>,
-    countdown_handle: Rc<RefCell<Option<Box<dyn TimerHandle>>>>,
-    blink_handle: Rc<RefCell<Option<Box<dyn TimerHandle>>>>,
-    remaining_time: Signal<i32>,
-    blinking_signal: Signal<bool>,
-    visible_signal: Signal<bool>,
+/// Lock mode: while `locked`, swallow every key press and click before any
+/// other handler sees it. Listening on the window in the capture phase runs
+/// ahead of the time's own handlers, the listeners here and the drag
+/// region. The lock chord locks; it or the passphrase unlocks, see `Lock`.
+pub fn setup_lock_guard(
+    settings: ReadSignal<Settings>,
+    locked: Signal<bool>,
+    input_mode: ReadSignal<bool>,
 ) {
     let window = web_sys::window().unwrap();
-    let paused = paused_signal.clone();
-    let provider = timer_provider.clone();
-    let countdown_handle = countdown_handle.clone();
-    let blink_handle = blink_handle.clone();
-    let remaining_time = remaining_time.clone();
-    let blinking = blinking_signal.clone();
-    let visible = visible_signal.clone();
-    let closure = Closure::wrap(Box::new(move |event: KeyboardEvent| {
-        if event.key() == "p" {
-            if paused.get() {
-                paused.set(false);
-                if !blinking.get() {
-                    start_countdown_timer(
-                        provider.clone(),
-                        &countdown_handle,
-                        &remaining_time,
-                        &blink_handle,
-                        &blinking,
-                        &visible,
-                    );
-                }
-            } else {
-                if blinking.get() {
-                    return;
-                }
-                paused.set(true);
-                if let Some(mut h) = countdown_handle.borrow_mut().take() {
-                    h.cancel();
-                }
-            }
+    // What was typed while locked, for the passphrase
+    let mut typed = String::new();
+    let keys = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+        let press = key_press(&event);
+        let was_locked = locked.get();
+        let toggled = settings.with(|s| {
+            // Not mid-entry, which would leave the field open and unusable
+            (!input_mode.get() && s.lock.chord_toggles(was_locked, &press))
+                || (was_locked && s.lock.type_key(&mut typed, &press.key))
+        });
+        if toggled {
+            typed.clear();
+            // At once, so the next key is already caught; the backend
+            // confirms with an event
+            locked.set(!was_locked);
+            lock_client::set(!was_locked);
+        }
+        if toggled || was_locked {
+            event.prevent_default();
+            event.stop_immediate_propagation();
         }
     }) as Box<dyn FnMut(_)>);
     window
-        .add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())
+        .add_event_listener_with_callback_and_bool("keydown", keys.as_ref().unchecked_ref(), true)
         .unwrap();
-    closure.forget();
+    keys.forget();
+
+    let mouse = Closure::wrap(Box::new(move |event: web_sys::Event| {
+        if locked.get() {
+            event.prevent_default();
+            event.stop_immediate_propagation();
+        }
+    }) as Box<dyn FnMut(_)>);
+    for kind in ["mousedown", "mouseup", "click", "dblclick"] {
+        window
+            .add_event_listener_with_callback_and_bool(kind, mouse.as_ref().unchecked_ref(), true)
+            .unwrap();
+    }
+    mouse.forget();
+}
+
+/// Run `f` once after `ms`
+pub fn set_timeout(ms: i32, f: impl FnOnce() + 'static) {
+    let callback = Closure::once_into_js(f);
+    if let Some(window) = web_sys::window() {
+        let _ = window
+            .set_timeout_with_callback_and_timeout_and_arguments_0(callback.unchecked_ref(), ms);
+    }
 }
 
 // Function to focus the timer input field after a short delay
@@ -168,9 +188,9 @@ pub fn focus_timer_input() {
     // Use setTimeout to focus after the DOM is updated
     let closure = wasm_bindgen::closure::Closure::wrap(Box::new(move || {
         if let Some(element) = document.get_element_by_id("timer-input") {
-            if let Some(input_element) = element.dyn_into::<HtmlInputElement>().ok() {
+            if let Ok(input_element) = element.dyn_into::<HtmlInputElement>() {
                 let _ = input_element.focus();
-                let _ = input_element.select(); // Select all text for easy editing
+                input_element.select(); // Select all text for easy editing
             }
         }
     }) as Box<dyn FnMut()>);
@@ -183,23 +203,9 @@ pub fn focus_timer_input() {
 }
 
 /// Setup input mode key handler (Enter/Escape) on window
-pub fn setup_input_mode_listener(
-    input_mode: Signal<bool>,
-    input_value: Signal<String>,
-    remaining_time: Signal<i32>,
-    timer_provider: Rc<dyn TimerProvider>,
-    countdown_handle: Rc<RefCell<Option<Box<dyn TimerHandle>>>>,
-    blink_handle: Rc<RefCell<Option<Box<dyn TimerHandle>>>>,
-    blinking_signal: Signal<bool>,
-    visible_signal: Signal<bool>,
-    paused_signal: Signal<bool>,
-    reset_time: Signal<Option<i32>>,
-) {
+pub fn setup_input_mode_listener(input_mode: ReadSignal<bool>, input_value: Signal<String>) {
     let window = web_sys::window().unwrap();
 
-    // Create a listener to track input mode changes
-    let input_mode_track = input_mode.clone();
-
     // Set appropriate listeners whenever input mode changes
     let doc = window.document().unwrap();
 
@@ -211,100 +217,18 @@ pub fn setup_input_mode_listener(
                     event.prevent_default();
                     event.stop_propagation();
 
-                    // Avoid reading and modifying signal values simultaneously, first get all needed values
-                    let input_str = input_value.with(|v| v.clone());
-
                     // Unparseable input is left in the field untouched
+                    let input_str = input_value.get_clone();
                     if let Some(total_seconds) = parse_time_input(&input_str) {
-                        // Set remaining time and exit input mode
-                        remaining_time.set(total_seconds);
-                        // Also update reset time for next click reset
-                        reset_time.set(Some(total_seconds));
-                        input_mode.set(false);
-
-                        // Save the time setting to LocalStorage
-                        save_remaining_seconds(total_seconds);
-
-                        // Cancel existing timers
-                        let mut cancelled_handles = false;
-                        if let Some(mut handle) = countdown_handle.borrow_mut().take() {
-                            handle.cancel();
-                            cancelled_handles = true;
-                        }
-                        if let Some(mut handle) = blink_handle.borrow_mut().take() {
-                            handle.cancel();
-                            cancelled_handles = true;
-                        }
-
-                        // Reset blink states
-                        blinking_signal.set(false);
-                        visible_signal.set(true);
-
-                        // Reset pause state
-                        paused_signal.set(false);
-
-                        // Start new countdown
-                        if cancelled_handles {
-                            // Ensure not reading and modifying signals simultaneously
-                            // Use a short delay to ensure all state updates are complete
-                            let remaining = remaining_time.clone();
-                            let provider = timer_provider.clone();
-                            let countdown = countdown_handle.clone();
-                            let blink = blink_handle.clone();
-                            let is_blinking = blinking_signal.clone();
-                            let is_visible = visible_signal.clone();
-
-                            let cb = wasm_bindgen::closure::Closure::wrap(Box::new(move || {
-                                start_countdown_timer(
-                                    provider.clone(),
-                                    &countdown,
-                                    &remaining,
-                                    &blink,
-                                    &is_blinking,
-                                    &is_visible,
-                                );
-                            })
-                                as Box<dyn FnMut()>);
-
-                            let _ = web_sys::window()
-                                .unwrap()
-                                .set_timeout_with_callback_and_timeout_and_arguments_0(
-                                    cb.as_ref().unchecked_ref(),
-                                    10, // Use 10ms delay to ensure state updates are complete
-                                );
-                            cb.forget();
-                        } else {
-                            // Start timer directly
-                            start_countdown_timer(
-                                timer_provider.clone(),
-                                &countdown_handle,
-                                &remaining_time,
-                                &blink_handle,
-                                &blinking_signal,
-                                &visible_signal,
-                            );
-                        }
+                        timer_client::dispatch(TimerCommand::SetDuration {
+                            seconds: total_seconds,
+                        });
                     }
                 }
                 "Escape" => {
                     event.prevent_default();
                     event.stop_propagation();
-
-                    // Exit input mode
-                    input_mode.set(false);
-
-                    // Resume timer if it was paused when entering input mode
-                    if paused_signal.get() && !blinking_signal.get() {
-                        paused_signal.set(false);
-                        start_countdown_timer(
-                            timer_provider.clone(),
-                            &countdown_handle,
-                            &remaining_time,
-                            &blink_handle,
-                            &blinking_signal,
-                            &visible_signal,
-                        );
-                    }
+                    timer_client::dispatch(TimerCommand::CancelEdit);
                 }
                 _ => {}
             }
@@ -316,114 +240,11 @@ pub fn setup_input_mode_listener(
         .unwrap();
     closure.forget();
 
-    // Monitor input mode changes, automatically select all text when entering input mode
-    let input_mode_effect = input_mode_track.clone();
-    let effect_closure = Closure::wrap(Box::new(move || {
-        if input_mode_effect.get() {
-            // Get input element and focus
-            if let Some(doc) = web_sys::window().and_then(|win| win.document()) {
-                if let Some(el) = doc.get_element_by_id("timer-input") {
-                    if let Ok(input) = el.dyn_into::<web_sys::HtmlInputElement>() {
-                        let _ = input.focus();
-                        input.select();
-                    }
-                }
-            }
+    // Focus and select the input whenever input mode opens, whether from 'f'
+    // or from another window or a reload
+    create_effect(move || {
+        if input_mode.get() {
+            focus_timer_input();
         }
-    }) as Box<dyn FnMut()>);
-
-    // Run setTimeout directly without intermediate function
-    if input_mode_track.get() {
-        let effect_fn = effect_closure.as_ref().unchecked_ref();
-        let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(
-            effect_fn, 50, // Run after 50 milliseconds
-        );
-    }
-
-    // Keep closure alive
-    effect_closure.forget();
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::config::{COUNTDOWN_INTERVAL_MS, INITIAL_SECONDS};
-    use crate::timer_provider::tests::FakeProvider;
-    use crate::timer_service::trigger_blink_timer;
-    use std::{cell::RefCell, rc::Rc};
-    use sycamore::prelude::create_signal;
-    use sycamore::reactive::create_root;
-
-    #[test]
-    fn test_handle_click_and_reset_not_blinking() {
-        let _ = create_root(|| {
-            let fake = FakeProvider::new();
-            let calls = fake.calls.clone();
-            let provider: Rc<dyn TimerProvider> = Rc::new(fake);
-            let countdown_handle = Rc::new(RefCell::new(None));
-            let blink_handle = Rc::new(RefCell::new(None));
-            let remaining = create_signal(999);
-            let blinking = create_signal(false);
-            let visible = create_signal(false);
-
-            handle_click_and_reset(
-                provider.clone(),
-                countdown_handle.clone(),
-                blink_handle.clone(),
-                remaining.clone(),
-                blinking.clone(),
-                visible.clone(),
-                create_signal(None),
-            );
-
-            // Should schedule countdown interval exactly once
-            assert_eq!(&*calls.borrow(), &[COUNTDOWN_INTERVAL_MS]);
-            // FakeProvider triggers immediately: remaining decremented from INITIAL_SECONDS
-            assert_eq!(remaining.get(), INITIAL_SECONDS - 1);
-            // Blink state reset
-            assert!(!blinking.get());
-            assert!(visible.get());
-            // No blink handle present
-            assert!(blink_handle.borrow().is_none());
-        });
-    }
-
-    #[test]
-    fn test_handle_click_and_reset_blinking() {
-        let _ = create_root(|| {
-            let fake = FakeProvider::new();
-            let calls = fake.calls.clone();
-            let provider: Rc<dyn TimerProvider> = Rc::new(fake);
-            let countdown_handle = Rc::new(RefCell::new(None));
-            let blink_handle = Rc::new(RefCell::new(None));
-            let remaining = create_signal(1);
-            let blinking = create_signal(false);
-            let visible = create_signal(true);
-            // simulate blinking started
-            trigger_blink_timer(provider.clone(), &blink_handle, &blinking, &visible);
-            // reset call history
-            calls.borrow_mut().clear();
-
-            // Now clicking should cancel blink and restart countdown
-            handle_click_and_reset(
-                provider.clone(),
-                countdown_handle.clone(),
-                blink_handle.clone(),
-                remaining.clone(),
-                blinking.clone(),
-                visible.clone(),
-                create_signal(None),
-            );
-
-            // Blink cancelled: handle taken
-            assert!(blink_handle.borrow().is_none());
-            // Blink signal cleared
-            assert!(!blinking.get());
-            assert!(visible.get());
-            // New countdown scheduled
-            assert_eq!(&*calls.borrow(), &[COUNTDOWN_INTERVAL_MS]);
-            // FakeProvider immediate callback: remaining from INITIAL_SECONDS
-            assert_eq!(remaining.get(), INITIAL_SECONDS - 1);
-        });
-    }
+    });
 }
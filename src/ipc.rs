@@ -0,0 +1,65 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+// `withGlobalTauri` in tauri.conf.json exposes the JS API on `window.__TAURI__`.
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], js_name = invoke, catch)]
+    async fn tauri_invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "event"], js_name = listen)]
+    async fn tauri_listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>) -> JsValue;
+}
+
+/// Fire-and-forget a backend command. Failures only matter to the backend,
+/// so they are dropped here rather than surfaced in the tiny window.
+pub fn invoke<A: Serialize>(cmd: &'static str, args: &A) {
+    let Ok(args) = serde_wasm_bindgen::to_value(args) else {
+        return;
+    };
+    wasm_bindgen_futures::spawn_local(async move {
+        let _ = tauri_invoke(cmd, args).await;
+    });
+}
+
+/// Call a backend command and decode its result, or `None` if it failed
+pub async fn call<A: Serialize, R: DeserializeOwned>(cmd: &str, args: &A) -> Option<R> {
+    let args = serde_wasm_bindgen::to_value(args).ok()?;
+    let result = tauri_invoke(cmd, args).await.ok()?;
+    serde_wasm_bindgen::from_value(result).ok()
+}
+
+/// Call a backend command that can refuse, decoding its result or the
+/// message it refused with
+pub async fn try_call<A: Serialize, R: DeserializeOwned>(cmd: &str, args: &A) -> Result<R, String> {
+    let args = serde_wasm_bindgen::to_value(args).map_err(|err| err.to_string())?;
+    match tauri_invoke(cmd, args).await {
+        Ok(result) => serde_wasm_bindgen::from_value(result).map_err(|err| err.to_string()),
+        Err(err) => Err(err.as_string().unwrap_or_else(|| format!("{err:?}"))),
+    }
+}
+
+/// Subscribe to a backend event for the lifetime of the page, decoding its
+/// payload. Payloads that do not decode are ignored.
+pub fn listen<T, F>(event: &'static str, mut handler: F)
+where
+    T: DeserializeOwned + 'static,
+    F: FnMut(T) + 'static,
+{
+    #[derive(serde::Deserialize)]
+    struct Event<T> {
+        payload: T,
+    }
+
+    let closure = Closure::wrap(Box::new(move |raw: JsValue| {
+        if let Ok(event) = serde_wasm_bindgen::from_value::<Event<T>>(raw) {
+            handler(event.payload);
+        }
+    }) as Box<dyn FnMut(JsValue)>);
+    wasm_bindgen_futures::spawn_local(async move {
+        tauri_listen(event, &closure).await;
+        // The page never unlistens, so the closure must outlive this future.
+        closure.forget();
+    });
+}
@@ -0,0 +1,30 @@
+//! Whether the timer window is locked. The backend keeps it, so it lasts
+//! across restarts and can be released when time is up; the window
+//! enforces it, see `event_ui::setup_lock_guard`.
+
+use crate::ipc;
+use sycamore::prelude::*;
+
+/// Event the backend emits whenever the timer is locked or unlocked
+const LOCK_EVENT: &str = "lock-changed";
+
+#[derive(serde::Serialize)]
+struct NoArgs {}
+
+/// Follow the backend's lock in `locked`
+pub fn connect(locked: Signal<bool>) {
+    ipc::listen(LOCK_EVENT, move |new: bool| locked.set(new));
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Some(current) = ipc::call::<_, bool>("get_lock", &NoArgs {}).await {
+            locked.set(current);
+        }
+    });
+}
+
+pub fn set(locked: bool) {
+    #[derive(serde::Serialize)]
+    struct Args {
+        locked: bool,
+    }
+    ipc::invoke("set_lock", &Args { locked });
+}
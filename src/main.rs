@@ -1,18 +1,27 @@
 mod app;
-mod config;
-mod event_logic;
+mod click_through_client;
 mod event_ui;
+mod ipc;
+mod lock_client;
+mod reduced_motion;
+mod settings_client;
+mod settings_view;
+mod sound;
 mod storage;
-mod style_utils;
-mod time_format;
-mod timer_logic;
-mod timer_provider;
-mod timer_service;
+mod timer_client;
 mod view_components;
+mod window_client;
 
 use app::App;
+use settings_view::SettingsApp;
 
 fn main() {
     console_error_panic_hook::set_once();
-    sycamore::render(App);
+    // The settings window loads this same page with a `#settings` fragment
+    let hash = web_sys::window().and_then(|window| window.location().hash().ok());
+    if hash.as_deref() == Some("#settings") {
+        sycamore::render(SettingsApp);
+    } else {
+        sycamore::render(App);
+    }
 }
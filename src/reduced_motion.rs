@@ -0,0 +1,23 @@
+//! Whether the system asks for reduced motion, which `ExpiryMotion::Auto`
+//! follows instead of blinking
+
+use sycamore::prelude::*;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+
+const QUERY: &str = "(prefers-reduced-motion: reduce)";
+
+/// Follow the system preference in `reduced`, including changes to it
+pub fn connect(reduced: Signal<bool>) {
+    let Some(list) = web_sys::window().and_then(|window| window.match_media(QUERY).ok().flatten())
+    else {
+        return;
+    };
+    reduced.set(list.matches());
+    let watched = list.clone();
+    let closure = Closure::wrap(Box::new(move |_: web_sys::Event| {
+        reduced.set(watched.matches());
+    }) as Box<dyn FnMut(_)>);
+    let _ = list.add_event_listener_with_callback("change", closure.as_ref().unchecked_ref());
+    closure.forget();
+}
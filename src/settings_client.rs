@@ -0,0 +1,48 @@
+//! The user's settings file, which the backend loads and watches. The timer
+//! window follows it; the settings window also writes it.
+
+use crate::ipc;
+use countdown_core::Settings;
+use serde::Deserialize;
+use sycamore::prelude::*;
+
+/// Event the backend emits whenever the settings file changes
+const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+
+/// Mirrors the backend's `SettingsStatus`
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct SettingsStatus {
+    pub settings: Settings,
+    pub path: String,
+    /// Why the file was rejected; the previous settings stay in force
+    pub error: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+struct NoArgs {}
+
+/// Follow the backend's settings in `status`, `None` until they arrive
+pub fn connect(status: Signal<Option<SettingsStatus>>) {
+    ipc::listen(SETTINGS_CHANGED_EVENT, move |new: SettingsStatus| {
+        status.set(Some(new));
+    });
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Some(current) = ipc::call::<_, SettingsStatus>("get_settings", &NoArgs {}).await {
+            status.set(Some(current));
+        }
+    });
+}
+
+/// Validate and write `settings`, which every window then receives
+pub async fn save(settings: Settings) -> Result<SettingsStatus, String> {
+    #[derive(serde::Serialize)]
+    struct Args {
+        settings: Settings,
+    }
+    ipc::try_call("save_settings", &Args { settings }).await
+}
+
+/// Open the settings window, or focus it if it is open
+pub fn open() {
+    ipc::invoke("open_settings", &NoArgs {});
+}
@@ -0,0 +1,830 @@
+//! The settings window. Every field saves on change, so the timer window
+//! picks it up at once; the backend validates and has the final word.
+
+use crate::event_ui::key_press;
+use crate::settings_client::{self, SettingsStatus};
+use countdown_core::chord::Chord;
+use countdown_core::event_logic::{format_time_input, parse_time_input};
+use countdown_core::keymap::Keymap;
+use countdown_core::theme::BUILTIN_THEMES;
+use countdown_core::time_format::format_time;
+use countdown_core::{
+    Alerts, Colors, Display, ExpiryCues, ExpiryMotion, Fade, Lock, Progress, ResetGesture,
+    ResetProtection, Settings, Shortcuts,
+};
+use sycamore::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::KeyboardEvent;
+
+/// The user theme that colours edited here are saved as
+const CUSTOM_THEME: &str = "custom";
+
+/// The form, one signal per field, as typed
+#[derive(Clone, Copy)]
+struct Form {
+    default_duration: Signal<String>,
+    overrun_limit: Signal<String>,
+    blink_interval_ms: Signal<String>,
+    theme: Signal<String>,
+    running_color: Signal<String>,
+    paused_color: Signal<String>,
+    warning_color: Signal<String>,
+    expired_color: Signal<String>,
+    editing_color: Signal<String>,
+    progress: Signal<Progress>,
+    display: Signal<Display>,
+    toggle_pause_key: Signal<String>,
+    enter_edit_key: Signal<String>,
+    cycle_display_key: Signal<String>,
+    grow_key: Signal<String>,
+    shrink_key: Signal<String>,
+    reset_key: Signal<String>,
+    snooze_key: Signal<String>,
+    undo_key: Signal<String>,
+    redo_key: Signal<String>,
+    open_settings_key: Signal<String>,
+    snooze: Signal<String>,
+    request_attention: Signal<bool>,
+    sound: Signal<bool>,
+    expiry_motion: Signal<ExpiryMotion>,
+    expiry_prefix: Signal<bool>,
+    expiry_invert: Signal<bool>,
+    expiry_border: Signal<bool>,
+    reset_gesture: Signal<ResetGesture>,
+    hold_ms: Signal<String>,
+    reset_only_when_up: Signal<bool>,
+    lock_chord: Signal<String>,
+    lock_passphrase: Signal<String>,
+    lock_release_on_expiry: Signal<bool>,
+    idle_opacity_percent: Signal<String>,
+    reveal_at: Signal<String>,
+    reveal_scale_percent: Signal<String>,
+}
+
+impl Form {
+    fn new() -> Self {
+        Self {
+            default_duration: create_signal(String::new()),
+            overrun_limit: create_signal(String::new()),
+            blink_interval_ms: create_signal(String::new()),
+            theme: create_signal(String::new()),
+            running_color: create_signal(String::new()),
+            paused_color: create_signal(String::new()),
+            warning_color: create_signal(String::new()),
+            expired_color: create_signal(String::new()),
+            editing_color: create_signal(String::new()),
+            progress: create_signal(Progress::default()),
+            display: create_signal(Display::default()),
+            toggle_pause_key: create_signal(String::new()),
+            enter_edit_key: create_signal(String::new()),
+            cycle_display_key: create_signal(String::new()),
+            grow_key: create_signal(String::new()),
+            shrink_key: create_signal(String::new()),
+            reset_key: create_signal(String::new()),
+            snooze_key: create_signal(String::new()),
+            undo_key: create_signal(String::new()),
+            redo_key: create_signal(String::new()),
+            open_settings_key: create_signal(String::new()),
+            snooze: create_signal(String::new()),
+            request_attention: create_signal(false),
+            sound: create_signal(false),
+            expiry_motion: create_signal(ExpiryMotion::default()),
+            expiry_prefix: create_signal(false),
+            expiry_invert: create_signal(false),
+            expiry_border: create_signal(false),
+            reset_gesture: create_signal(ResetGesture::default()),
+            hold_ms: create_signal(String::new()),
+            reset_only_when_up: create_signal(false),
+            lock_chord: create_signal(String::new()),
+            lock_passphrase: create_signal(String::new()),
+            lock_release_on_expiry: create_signal(false),
+            idle_opacity_percent: create_signal(String::new()),
+            reveal_at: create_signal(String::new()),
+            reveal_scale_percent: create_signal(String::new()),
+        }
+    }
+
+    fn fill(&self, settings: &Settings) {
+        self.default_duration
+            .set(format_time(settings.default_seconds));
+        self.overrun_limit
+            .set(format_time(settings.overrun_limit_seconds));
+        self.blink_interval_ms
+            .set(settings.blink_interval_ms.to_string());
+        self.theme.set(settings.theme.clone());
+        self.fill_colors(&settings.active_theme().colors);
+        self.progress.set(settings.progress);
+        self.display.set(settings.display);
+        self.toggle_pause_key
+            .set(settings.shortcuts.toggle_pause.clone());
+        self.enter_edit_key
+            .set(settings.shortcuts.enter_edit.clone());
+        self.cycle_display_key
+            .set(settings.shortcuts.cycle_display.clone());
+        self.grow_key.set(settings.shortcuts.grow.clone());
+        self.shrink_key.set(settings.shortcuts.shrink.clone());
+        self.reset_key.set(settings.shortcuts.reset.clone());
+        self.snooze_key.set(settings.shortcuts.snooze.clone());
+        self.undo_key.set(settings.shortcuts.undo.clone());
+        self.redo_key.set(settings.shortcuts.redo.clone());
+        self.open_settings_key
+            .set(settings.shortcuts.open_settings.clone());
+        self.snooze.set(format_time(settings.snooze_seconds));
+        self.request_attention
+            .set(settings.alerts.request_attention);
+        self.sound.set(settings.alerts.sound);
+        self.expiry_motion.set(settings.expiry.motion);
+        self.expiry_prefix.set(settings.expiry.prefix);
+        self.expiry_invert.set(settings.expiry.invert);
+        self.expiry_border.set(settings.expiry.border);
+        self.reset_gesture.set(settings.reset.gesture);
+        self.hold_ms.set(settings.reset.hold_ms.to_string());
+        self.reset_only_when_up.set(settings.reset.only_when_up);
+        self.lock_chord.set(settings.lock.chord.clone());
+        self.lock_passphrase.set(settings.lock.passphrase.clone());
+        self.lock_release_on_expiry
+            .set(settings.lock.release_on_expiry);
+        self.idle_opacity_percent
+            .set(percent(settings.fade.idle_opacity));
+        self.reveal_at
+            .set(format_time(settings.fade.reveal_seconds));
+        self.reveal_scale_percent
+            .set(percent(settings.fade.reveal_scale));
+    }
+
+    fn fill_colors(&self, colors: &Colors) {
+        self.running_color.set(colors.running.clone());
+        self.paused_color.set(colors.paused.clone());
+        self.warning_color.set(colors.warning.clone());
+        self.expired_color.set(colors.expired.clone());
+        self.editing_color.set(colors.editing.clone());
+    }
+
+    fn colors(&self) -> Colors {
+        Colors {
+            running: self.running_color.get_clone(),
+            paused: self.paused_color.get_clone(),
+            warning: self.warning_color.get_clone(),
+            expired: self.expired_color.get_clone(),
+            editing: self.editing_color.get_clone(),
+        }
+    }
+
+    /// `base` with the form's values, or what cannot be read. Colours that
+    /// differ from the chosen theme's are saved as the `custom` theme.
+    fn read(&self, base: Settings) -> Result<Settings, String> {
+        let mut themes = base.themes.clone();
+        let mut theme_name = self.theme.get_clone();
+        let mut theme = Settings {
+            theme: theme_name.clone(),
+            ..base.clone()
+        }
+        .active_theme();
+        let colors = self.colors();
+        if colors != theme.colors {
+            theme.colors = colors;
+            themes.insert(CUSTOM_THEME.into(), theme);
+            theme_name = CUSTOM_THEME.into();
+        }
+
+        let duration = |field: Signal<String>, name: &str| {
+            parse_time_input(&field.get_clone()).ok_or(format!("{name} must be mm:ss"))
+        };
+        let fraction = |field: Signal<String>, name: &str| {
+            field
+                .get_clone()
+                .trim()
+                .parse::<f32>()
+                .map(|percent| percent / 100.0)
+                .map_err(|_| format!("{name} must be a percentage"))
+        };
+        Ok(Settings {
+            default_seconds: duration(self.default_duration, "Default duration")?,
+            overrun_limit_seconds: duration(self.overrun_limit, "Overrun limit")?,
+            snooze_seconds: duration(self.snooze, "Snooze")?,
+            blink_interval_ms: self
+                .blink_interval_ms
+                .get_clone()
+                .trim()
+                .parse()
+                .map_err(|_| "Blink interval must be a whole number of milliseconds")?,
+            theme: theme_name,
+            themes,
+            progress: self.progress.get(),
+            display: self.display.get(),
+            shortcuts: Shortcuts {
+                toggle_pause: self.toggle_pause_key.get_clone(),
+                enter_edit: self.enter_edit_key.get_clone(),
+                cycle_display: self.cycle_display_key.get_clone(),
+                grow: self.grow_key.get_clone(),
+                shrink: self.shrink_key.get_clone(),
+                reset: self.reset_key.get_clone(),
+                snooze: self.snooze_key.get_clone(),
+                undo: self.undo_key.get_clone(),
+                redo: self.redo_key.get_clone(),
+                open_settings: self.open_settings_key.get_clone(),
+            },
+            alerts: Alerts {
+                request_attention: self.request_attention.get(),
+                sound: self.sound.get(),
+            },
+            expiry: ExpiryCues {
+                motion: self.expiry_motion.get(),
+                prefix: self.expiry_prefix.get(),
+                invert: self.expiry_invert.get(),
+                border: self.expiry_border.get(),
+            },
+            reset: ResetProtection {
+                gesture: self.reset_gesture.get(),
+                hold_ms: self
+                    .hold_ms
+                    .get_clone()
+                    .trim()
+                    .parse()
+                    .map_err(|_| "Hold time must be a whole number of milliseconds")?,
+                only_when_up: self.reset_only_when_up.get(),
+            },
+            lock: Lock {
+                chord: self.lock_chord.get_clone().trim().into(),
+                passphrase: self.lock_passphrase.get_clone(),
+                release_on_expiry: self.lock_release_on_expiry.get(),
+            },
+            fade: Fade {
+                idle_opacity: fraction(self.idle_opacity_percent, "Idle opacity")?,
+                reveal_seconds: duration(self.reveal_at, "Show fully from")?,
+                reveal_scale: fraction(self.reveal_scale_percent, "Enlarge to")?,
+            },
+            ..base
+        })
+    }
+}
+
+/// `fraction` as a whole percentage, for the form
+fn percent(fraction: f32) -> String {
+    format!("{:.0}", fraction * 100.0)
+}
+
+#[component]
+pub fn SettingsApp() -> View {
+    let status = create_signal(None::<SettingsStatus>);
+    let form = Form::new();
+    // Outcome of the last save, or why the form could not be saved
+    let message = create_signal(None::<Result<String, String>>);
+
+    // Refill the form from every status, including changes to the file
+    create_effect(move || {
+        status.with(|status| {
+            if let Some(status) = status {
+                form.fill(&status.settings);
+            }
+        })
+    });
+    on_mount(move || settings_client::connect(status));
+
+    let save = move || {
+        let Some(current) = status.get_clone() else {
+            return;
+        };
+        let settings = match form.read(current.settings) {
+            Ok(settings) => settings,
+            Err(err) => return message.set(Some(Err(err))),
+        };
+        wasm_bindgen_futures::spawn_local(async move {
+            match settings_client::save(settings).await {
+                Ok(saved) => {
+                    message.set(Some(Ok(format!("Saved to {}", saved.path))));
+                    status.set(Some(saved));
+                }
+                Err(err) => message.set(Some(Err(err))),
+            }
+        });
+    };
+
+    // Switching theme shows its colours rather than keeping the old ones
+    let choose_theme = move |ev: web_sys::Event| {
+        let Some(select) = ev
+            .target()
+            .and_then(|t| t.dyn_into::<web_sys::HtmlSelectElement>().ok())
+        else {
+            return;
+        };
+        let Some(current) = status.get_clone() else {
+            return;
+        };
+        let chosen = Settings {
+            theme: select.value(),
+            ..current.settings
+        };
+        form.theme.set(chosen.theme.clone());
+        form.fill_colors(&chosen.active_theme().colors);
+        save();
+    };
+    let theme_names = create_memo(move || {
+        let mut names: Vec<String> = BUILTIN_THEMES.iter().map(|&name| name.into()).collect();
+        status.with(|status| {
+            if let Some(status) = status {
+                for name in status.settings.themes.keys() {
+                    if !names.contains(name) {
+                        names.push(name.clone());
+                    }
+                }
+            }
+        });
+        names
+    });
+
+    let choose_progress = move |ev: web_sys::Event| {
+        let chosen = ev
+            .target()
+            .and_then(|t| t.dyn_into::<web_sys::HtmlSelectElement>().ok())
+            .and_then(|select| Progress::from_name(&select.value()));
+        if let Some(progress) = chosen {
+            form.progress.set(progress);
+            save();
+        }
+    };
+
+    let choose_display = move |ev: web_sys::Event| {
+        let chosen = ev
+            .target()
+            .and_then(|t| t.dyn_into::<web_sys::HtmlSelectElement>().ok())
+            .and_then(|select| Display::from_name(&select.value()));
+        if let Some(display) = chosen {
+            form.display.set(display);
+            save();
+        }
+    };
+
+    let choose_expiry_motion = move |ev: web_sys::Event| {
+        let chosen = ev
+            .target()
+            .and_then(|t| t.dyn_into::<web_sys::HtmlSelectElement>().ok())
+            .and_then(|select| ExpiryMotion::from_name(&select.value()));
+        if let Some(motion) = chosen {
+            form.expiry_motion.set(motion);
+            save();
+        }
+    };
+
+    let choose_reset_gesture = move |ev: web_sys::Event| {
+        let chosen = ev
+            .target()
+            .and_then(|t| t.dyn_into::<web_sys::HtmlSelectElement>().ok())
+            .and_then(|select| ResetGesture::from_name(&select.value()));
+        if let Some(gesture) = chosen {
+            form.reset_gesture.set(gesture);
+            save();
+        }
+    };
+
+    // Typing over a duration keeps its colon, as in the timer window
+    let duration_input = move |field: Signal<String>| {
+        move |ev: web_sys::Event| {
+            if let Some(input) = ev
+                .target()
+                .and_then(|t| t.dyn_into::<web_sys::HtmlInputElement>().ok())
+            {
+                field.set(format_time_input(&input.value()));
+            }
+        }
+    };
+    // Shortcut fields record the next key pressed rather than text
+    // A key field takes the next key pressed, with any modifiers held
+    let key_input = move |field: Signal<String>| {
+        move |ev: KeyboardEvent| {
+            if ev.key() == "Tab" {
+                return;
+            }
+            ev.prevent_default();
+            if let Some(chord) = Chord::from_press(&key_press(&ev)) {
+                field.set(chord.to_string());
+                save();
+            }
+        }
+    };
+    // Keys bound twice, as the form stands; saving them is refused
+    let clashes = create_memo(move || {
+        let Some(base) = status.with(|s| s.as_ref().map(|s| s.settings.clone())) else {
+            return Vec::new();
+        };
+        let Ok(settings) = form.read(base) else {
+            return Vec::new();
+        };
+        Keymap::new(&settings)
+            .conflicts()
+            .into_iter()
+            .map(|(binding, other)| {
+                format!(
+                    "{} and {} are both {}",
+                    binding.action.label(),
+                    other.action.label(),
+                    binding.text
+                )
+            })
+            .collect::<Vec<_>>()
+    });
+
+    view! {
+        div(class="settings") {
+            h1 { "Settings" }
+            (if status.with(Option::is_none) {
+                view! { p { "Loading…" } }
+            } else {
+                view! {
+                    fieldset {
+                        legend { "Timer" }
+                        label {
+                            "Default duration"
+                            input(
+                                bind:value=form.default_duration,
+                                on:input=duration_input(form.default_duration),
+                                on:change=move |_| save(),
+                            )
+                        }
+                        label {
+                            "Count past zero for up to"
+                            input(
+                                bind:value=form.overrun_limit,
+                                on:input=duration_input(form.overrun_limit),
+                                on:change=move |_| save(),
+                            )
+                        }
+                        label {
+                            "Blink interval (ms)"
+                            input(
+                                r#type="number",
+                                min="50",
+                                max="10000",
+                                bind:value=form.blink_interval_ms,
+                                on:change=move |_| save(),
+                            )
+                        }
+                    }
+                    fieldset {
+                        legend { "Appearance" }
+                        label {
+                            "Theme"
+                            select(on:change=choose_theme) {
+                                Indexed(
+                                    list=theme_names,
+                                    view=move |name| {
+                                        let value = name.clone();
+                                        let this = name.clone();
+                                        let selected = move || form.theme.with(|theme| *theme == this);
+                                        view! {
+                                            option(value=value, selected=selected) { (name) }
+                                        }
+                                    },
+                                )
+                            }
+                        }
+                        label {
+                            "Display"
+                            select(on:change=choose_display) {
+                                (Display::ALL
+                                    .into_iter()
+                                    .map(|display| {
+                                        let selected = move || form.display.get() == display;
+                                        view! {
+                                            option(value=display.as_str(), selected=selected) {
+                                                (display_label(display))
+                                            }
+                                        }
+                                    })
+                                    .collect::<Vec<_>>())
+                            }
+                        }
+                        label {
+                            "Progress"
+                            select(on:change=choose_progress) {
+                                (Progress::ALL
+                                    .into_iter()
+                                    .map(|progress| {
+                                        let selected = move || form.progress.get() == progress;
+                                        view! {
+                                            option(value=progress.as_str(), selected=selected) {
+                                                (progress_label(progress))
+                                            }
+                                        }
+                                    })
+                                    .collect::<Vec<_>>())
+                            }
+                        }
+                        label {
+                            "Running"
+                            input(bind:value=form.running_color, on:change=move |_| save())
+                        }
+                        label {
+                            "Paused"
+                            input(bind:value=form.paused_color, on:change=move |_| save())
+                        }
+                        label {
+                            "Warning"
+                            input(bind:value=form.warning_color, on:change=move |_| save())
+                        }
+                        label {
+                            "Time up"
+                            input(bind:value=form.expired_color, on:change=move |_| save())
+                        }
+                        label {
+                            "Entering time"
+                            input(bind:value=form.editing_color, on:change=move |_| save())
+                        }
+                    }
+                    fieldset {
+                        legend { "Fading" }
+                        label {
+                            "Idle opacity (%)"
+                            input(
+                                r#type="number",
+                                min="10",
+                                max="100",
+                                bind:value=form.idle_opacity_percent,
+                                on:change=move |_| save(),
+                            )
+                        }
+                        label {
+                            "Show fully from"
+                            input(
+                                bind:value=form.reveal_at,
+                                on:input=duration_input(form.reveal_at),
+                                on:change=move |_| save(),
+                            )
+                        }
+                        label {
+                            "Then enlarge to (%)"
+                            input(
+                                r#type="number",
+                                min="100",
+                                max="200",
+                                bind:value=form.reveal_scale_percent,
+                                on:change=move |_| save(),
+                            )
+                        }
+                    }
+                    fieldset {
+                        legend { "Resetting" }
+                        label {
+                            "Reset by"
+                            select(on:change=choose_reset_gesture) {
+                                (ResetGesture::ALL
+                                    .into_iter()
+                                    .map(|gesture| {
+                                        let selected = move || form.reset_gesture.get() == gesture;
+                                        view! {
+                                            option(value=gesture.as_str(), selected=selected) {
+                                                (reset_gesture_label(gesture))
+                                            }
+                                        }
+                                    })
+                                    .collect::<Vec<_>>())
+                            }
+                        }
+                        label {
+                            "Hold for (ms)"
+                            input(
+                                r#type="number",
+                                min="200",
+                                max="5000",
+                                step="100",
+                                bind:value=form.hold_ms,
+                                on:change=move |_| save(),
+                            )
+                        }
+                        label(class="check") {
+                            input(
+                                r#type="checkbox",
+                                bind:checked=form.reset_only_when_up,
+                                on:change=move |_| save(),
+                            )
+                            "Only once time is up"
+                        }
+                    }
+                    fieldset {
+                        legend { "Locking" }
+                        label {
+                            "Lock with"
+                            input(
+                                readonly=true,
+                                bind:value=form.lock_chord,
+                                on:keydown=key_input(form.lock_chord),
+                            )
+                        }
+                        label {
+                            "Passphrase to unlock"
+                            input(
+                                r#type="password",
+                                placeholder="none: the same keys unlock",
+                                bind:value=form.lock_passphrase,
+                                on:change=move |_| save(),
+                            )
+                        }
+                        label(class="check") {
+                            input(
+                                r#type="checkbox",
+                                bind:checked=form.lock_release_on_expiry,
+                                on:change=move |_| save(),
+                            )
+                            "Unlock once time is up"
+                        }
+                    }
+                    fieldset {
+                        legend { "Shortcuts" }
+                        label {
+                            "Pause / resume"
+                            input(
+                                readonly=true,
+                                bind:value=form.toggle_pause_key,
+                                on:keydown=key_input(form.toggle_pause_key),
+                            )
+                        }
+                        label {
+                            "Enter time"
+                            input(
+                                readonly=true,
+                                bind:value=form.enter_edit_key,
+                                on:keydown=key_input(form.enter_edit_key),
+                            )
+                        }
+                        label {
+                            "Reset"
+                            input(
+                                readonly=true,
+                                bind:value=form.reset_key,
+                                on:keydown=key_input(form.reset_key),
+                            )
+                        }
+                        label {
+                            "Snooze"
+                            input(
+                                readonly=true,
+                                bind:value=form.snooze_key,
+                                on:keydown=key_input(form.snooze_key),
+                            )
+                        }
+                        label {
+                            "Next display"
+                            input(
+                                readonly=true,
+                                bind:value=form.cycle_display_key,
+                                on:keydown=key_input(form.cycle_display_key),
+                            )
+                        }
+                        label {
+                            "Larger window"
+                            input(
+                                readonly=true,
+                                bind:value=form.grow_key,
+                                on:keydown=key_input(form.grow_key),
+                            )
+                        }
+                        label {
+                            "Smaller window"
+                            input(
+                                readonly=true,
+                                bind:value=form.shrink_key,
+                                on:keydown=key_input(form.shrink_key),
+                            )
+                        }
+                        label {
+                            "Undo"
+                            input(
+                                readonly=true,
+                                bind:value=form.undo_key,
+                                on:keydown=key_input(form.undo_key),
+                            )
+                        }
+                        label {
+                            "Redo"
+                            input(
+                                readonly=true,
+                                bind:value=form.redo_key,
+                                on:keydown=key_input(form.redo_key),
+                            )
+                        }
+                        label {
+                            "Open settings"
+                            input(
+                                readonly=true,
+                                bind:value=form.open_settings_key,
+                                on:keydown=key_input(form.open_settings_key),
+                            )
+                        }
+                        (clashes
+                            .get_clone()
+                            .into_iter()
+                            .map(|clash| view! { p(class="clash") { (clash) } })
+                            .collect::<Vec<_>>())
+                    }
+                    fieldset {
+                        legend { "When time is up" }
+                        label {
+                            "Snooze for"
+                            input(
+                                bind:value=form.snooze,
+                                on:input=duration_input(form.snooze),
+                                on:change=move |_| save(),
+                            )
+                        }
+                        label(class="check") {
+                            input(
+                                r#type="checkbox",
+                                bind:checked=form.request_attention,
+                                on:change=move |_| save(),
+                            )
+                            "Ask for attention"
+                        }
+                        label(class="check") {
+                            input(
+                                r#type="checkbox",
+                                bind:checked=form.sound,
+                                on:change=move |_| save(),
+                            )
+                            "Play a sound"
+                        }
+                        label {
+                            "Motion"
+                            select(on:change=choose_expiry_motion) {
+                                (ExpiryMotion::ALL
+                                    .into_iter()
+                                    .map(|motion| {
+                                        let selected = move || form.expiry_motion.get() == motion;
+                                        view! {
+                                            option(value=motion.as_str(), selected=selected) {
+                                                (expiry_motion_label(motion))
+                                            }
+                                        }
+                                    })
+                                    .collect::<Vec<_>>())
+                            }
+                        }
+                        label(class="check") {
+                            input(
+                                r#type="checkbox",
+                                bind:checked=form.expiry_prefix,
+                                on:change=move |_| save(),
+                            )
+                            "Show an alarm icon"
+                        }
+                        label(class="check") {
+                            input(
+                                r#type="checkbox",
+                                bind:checked=form.expiry_invert,
+                                on:change=move |_| save(),
+                            )
+                            "Invert the colours"
+                        }
+                        label(class="check") {
+                            input(
+                                r#type="checkbox",
+                                bind:checked=form.expiry_border,
+                                on:change=move |_| save(),
+                            )
+                            "Dashed outline"
+                        }
+                    }
+                }
+            })
+            (match message.get_clone() {
+                Some(Ok(text)) => view! { p(class="settings-message") { (text) } },
+                Some(Err(text)) => view! { p(class="settings-message error") { (text) } },
+                None => view! {},
+            })
+        }
+    }
+}
+
+fn progress_label(progress: Progress) -> &'static str {
+    match progress {
+        Progress::None => "None",
+        Progress::Ring => "Ring",
+        Progress::Bar => "Bar",
+        Progress::Fill => "Background fill",
+    }
+}
+
+fn reset_gesture_label(gesture: ResetGesture) -> &'static str {
+    match gesture {
+        ResetGesture::Click => "Click",
+        ResetGesture::DoubleClick => "Double-click",
+        ResetGesture::Hold => "Press and hold",
+        ResetGesture::ModifierClick => "Shift / Ctrl / Alt + click",
+    }
+}
+
+fn expiry_motion_label(motion: ExpiryMotion) -> &'static str {
+    match motion {
+        ExpiryMotion::Auto => "Blink, or pulse with reduced motion",
+        ExpiryMotion::Blink => "Blink",
+        ExpiryMotion::Pulse => "Gentle pulse",
+        ExpiryMotion::Still => "Still",
+    }
+}
+
+fn display_label(display: Display) -> &'static str {
+    match display {
+        Display::Digits => "Digits",
+        Display::Dial => "Analog dial",
+        Display::Flip => "Flip clock",
+    }
+}
@@ -0,0 +1,51 @@
+//! The tone played when time is up, synthesised so there is no audio asset
+
+use std::cell::RefCell;
+use web_sys::{AudioContext, OscillatorType};
+
+const FREQUENCY_HZ: f32 = 880.0;
+const DURATION_S: f64 = 0.6;
+
+thread_local! {
+    /// Made on the first tone and kept: browsers allow only a handful of
+    /// live contexts, and warnings and escalation play many tones
+    static CONTEXT: RefCell<Option<AudioContext>> = const { RefCell::new(None) };
+}
+
+/// Play a short fading beep. Failure, e.g. no audio device, stays silent.
+pub fn play_expiry_tone() {
+    let _ = try_play();
+}
+
+/// The shared context, made if need be and resumed in case the webview
+/// suspended it
+fn context() -> Result<AudioContext, wasm_bindgen::JsValue> {
+    CONTEXT.with(|shared| {
+        let mut shared = shared.borrow_mut();
+        let context = match shared.as_ref() {
+            Some(context) => context.clone(),
+            None => shared.insert(AudioContext::new()?).clone(),
+        };
+        let _ = context.resume();
+        Ok(context)
+    })
+}
+
+fn try_play() -> Result<(), wasm_bindgen::JsValue> {
+    let context = context()?;
+    let oscillator = context.create_oscillator()?;
+    oscillator.set_type(OscillatorType::Sine);
+    oscillator.frequency().set_value(FREQUENCY_HZ);
+
+    let gain = context.create_gain()?;
+    let now = context.current_time();
+    gain.gain().set_value_at_time(0.2, now)?;
+    gain.gain()
+        .exponential_ramp_to_value_at_time(0.001, now + DURATION_S)?;
+
+    oscillator.connect_with_audio_node(&gain)?;
+    gain.connect_with_audio_node(&context.destination())?;
+    oscillator.start()?;
+    oscillator.stop_with_when(now + DURATION_S)?;
+    Ok(())
+}
@@ -1,39 +1,58 @@
-use crate::config::INITIAL_SECONDS;
-
-const TIMER_REMAINING_SECONDS_KEY: &str = "countdown_timer_remaining_seconds";
-
-/// Read remaining seconds from LocalStorage
-/// If no stored value is found or an error occurs, returns the default value INITIAL_SECONDS
-pub fn load_remaining_seconds() -> i32 {
-    // Try to get the value from localStorage
-    if let Some(storage) = get_local_storage() {
-        if let Ok(Some(value)) = storage.get_item(TIMER_REMAINING_SECONDS_KEY) {
-            if let Ok(seconds) = value.parse::<i32>() {
-                if seconds >= 0 {
-                    return seconds;
-                }
-            }
-        }
+//! The webview's LocalStorage as a `Storage`, holding the state document.
+//! The backend keeps its own copy, which wins when both have one.
+
+use countdown_core::storage::{self, Storage, StorageError, StoredState};
+
+/// The webview's LocalStorage
+pub struct LocalStorage(web_sys::Storage);
+
+impl LocalStorage {
+    /// `None` where the webview offers none
+    pub fn open() -> Option<Self> {
+        web_sys::window()?.local_storage().ok()?.map(Self)
     }
+}
 
-    // Return default if anything fails
-    INITIAL_SECONDS
+fn js_error(err: wasm_bindgen::JsValue) -> StorageError {
+    StorageError::new(format!("{err:?}"))
 }
 
-/// Save remaining seconds to LocalStorage
-pub fn save_remaining_seconds(seconds: i32) {
-    if let Some(storage) = get_local_storage() {
-        let _ = storage.set_item(TIMER_REMAINING_SECONDS_KEY, &seconds.to_string());
+impl Storage for LocalStorage {
+    fn read(&self, key: &str) -> Result<Option<String>, StorageError> {
+        self.0.get_item(key).map_err(js_error)
+    }
+
+    fn write(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        self.0.set_item(key, value).map_err(js_error)
     }
-}
 
-/// Get LocalStorage instance
-fn get_local_storage() -> Option<web_sys::Storage> {
-    match web_sys::window() {
-        Some(window) => match window.local_storage() {
-            Ok(Some(storage)) => Some(storage),
-            _ => None,
-        },
-        None => None,
+    fn remove(&self, key: &str) -> Result<(), StorageError> {
+        self.0.remove_item(key).map_err(js_error)
     }
 }
+
+/// The stored state, migrated if need be. Anything unreadable is kept as a
+/// backup by `storage::load`; the timer carries on without it.
+fn load_state(storage: &LocalStorage) -> StoredState {
+    storage::load(storage)
+        .map(|loaded| loaded.state)
+        .unwrap_or_default()
+}
+
+/// Read the most recently set duration, or `None` if nothing usable is
+/// stored and the backend's default applies
+pub fn load_remaining_seconds() -> Option<i32> {
+    load_state(&LocalStorage::open()?).duration()
+}
+
+/// Remember `seconds` as the most recently set duration
+pub fn save_remaining_seconds(seconds: i32) {
+    let Some(storage) = LocalStorage::open() else {
+        return;
+    };
+    let state = StoredState {
+        duration_seconds: Some(seconds),
+        ..load_state(&storage)
+    };
+    let _ = storage::save(&storage, &state);
+}
@@ -0,0 +1,40 @@
+//! Client side of the timer, which runs in the backend. Windows only draw
+//! the state it sends and forward keys and clicks to it as commands.
+
+use crate::ipc;
+use countdown_core::dispatch::Dispatched;
+use countdown_core::{TimerCommand, TimerSnapshot};
+use serde::Serialize;
+use sycamore::prelude::*;
+
+/// Event the backend emits whenever the timer state changes
+const TIMER_STATE_EVENT: &str = "timer-state";
+
+#[derive(Serialize)]
+struct Args {
+    command: TimerCommand,
+}
+
+/// Follow the backend timer in `timer`, attaching with the duration saved in
+/// LocalStorage. The first window to attach starts the countdown; any later
+/// one, including this one after a reload, picks up where it is.
+pub fn connect(timer: Signal<Option<TimerSnapshot>>, saved_seconds: Option<i32>) {
+    ipc::listen(TIMER_STATE_EVENT, move |snapshot: TimerSnapshot| {
+        timer.set(Some(snapshot));
+    });
+
+    wasm_bindgen_futures::spawn_local(async move {
+        let args = Args {
+            command: TimerCommand::Attach { saved_seconds },
+        };
+        if let Some(dispatched) = ipc::call::<_, Dispatched>("timer_dispatch", &args).await {
+            timer.set(Some(dispatched.state));
+        }
+    });
+}
+
+/// Send a key's or click's command to the timer. The new state arrives as
+/// a `timer-state` event; a refused command changes nothing to show.
+pub fn dispatch(command: TimerCommand) {
+    ipc::invoke("timer_dispatch", &Args { command });
+}
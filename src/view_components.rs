@@ -1,80 +1,334 @@
-use crate::event_ui::{handle_click_and_reset, handle_timer_input};
-use crate::style_utils::compute_timer_style;
-use crate::timer_provider::{TimerHandle, TimerProvider};
-use std::cell::RefCell;
-use std::rc::Rc;
+use crate::event_ui::{handle_timer_input, set_timeout};
+use crate::timer_client;
+use countdown_core::announce::spoken_time;
+use countdown_core::dial::{dial_fraction, sector_path};
+use countdown_core::style_utils::{compute_input_style, compute_text_style, compute_timer_style};
+use countdown_core::{
+    Display, ExpiryCues, Lock, Progress, ResetGesture, ResetProtection, Theme, TimerCommand,
+    WarningStage,
+};
 use sycamore::prelude::*;
-use web_sys::MouseEvent;
+use web_sys::{KeyboardEvent, MouseEvent};
+
+/// Two presses of `Enter` or `Space` this close together count as a
+/// double-click, as two clicks would by a typical system setting
+const DOUBLE_PRESS_MS: f64 = 500.0;
 
 /// Helper function to create timer input view
-pub fn create_timer_input_view(input_value: Signal<String>, input_mode: Signal<bool>) -> View {
+pub fn create_timer_input_view(input_value: Signal<String>, theme: ReadSignal<Theme>) -> View {
     view! {
         div(data-tauri-drag-region="false", class="input-container") {
             input(
                 bind:value=input_value,
                 id="timer-input",
                 class="timer-input",
+                aria-label="New time, minutes and seconds",
+                inputmode="numeric",
+                style=move || theme.with(compute_input_style),
                 // Add input event handler for automatic colon insertion
-                on:input={
-                    let input_value_clone = input_value.clone();
-                    move |ev| handle_timer_input(ev, input_value_clone.clone())
-                },
+                on:input=move |ev| handle_timer_input(ev, input_value),
                 // Prevent right-click context menu in input mode
                 on:contextmenu=|ev: MouseEvent| ev.prevent_default(),
-                on:blur={
-                    let _input_mode_clone = input_mode.clone();
-                    move |_| {
-                        // Optionally exit input mode when focus is lost
-                        // Uncomment if you want this behavior
-                        // _input_mode_clone.set(false);
-                    }
+                on:blur=move |_| {
+                    // Optionally exit input mode when focus is lost
+                    // Uncomment if you want this behavior
+                    // crate::timer_client::dispatch(TimerCommand::CancelEdit);
                 }
             )
         }
     }
 }
 
+/// What every display renderer draws from
+#[derive(Clone, Copy)]
+pub struct DisplayState {
+    pub formatted_time: ReadSignal<String>,
+    pub remaining: ReadSignal<i32>,
+}
+
+/// Whether time is up and how that is shown
+#[derive(Clone, Copy)]
+pub struct ExpiryState {
+    pub blinking: ReadSignal<bool>,
+    /// Shown in the blink's current phase, always unless blinking
+    pub visible: ReadSignal<bool>,
+    /// With the motion resolved
+    pub cues: ReadSignal<ExpiryCues>,
+}
+
+/// Draws the time inside the display, which supplies its colour, blink and
+/// click handler, so renderers only draw in `currentColor`
+type Renderer = fn(DisplayState) -> View;
+
+fn renderer(display: Display) -> Renderer {
+    match display {
+        Display::Digits => render_digits,
+        Display::Dial => render_dial,
+        Display::Flip => render_flip,
+    }
+}
+
+fn render_digits(state: DisplayState) -> View {
+    view! { (state.formatted_time) }
+}
+
+fn render_dial(state: DisplayState) -> View {
+    let sector = move || {
+        let remaining = state.remaining.get();
+        sector_path(dial_fraction(remaining), remaining < 0)
+    };
+    view! {
+        svg(class="dial", viewBox="-50 -50 100 100") {
+            circle(class="dial-face", r="48")
+            path(class="dial-sector", d=sector)
+        }
+    }
+}
+
+fn render_flip(state: DisplayState) -> View {
+    view! {
+        span(class="flip") {
+            (state.formatted_time.with(|time| {
+                time.chars()
+                    .map(|c| {
+                        let class = if c.is_ascii_digit() { "flip-card" } else { "flip-separator" };
+                        view! { span(class=class) { (c.to_string()) } }
+                    })
+                    .collect::<Vec<_>>()
+            }))
+        }
+    }
+}
+
 /// Helper function to create timer display view
 pub fn create_timer_display_view(
-    formatted_time: ReadSignal<String>,
-    ui_blink_active: Signal<bool>,
-    ui_blink_visible: Signal<bool>,
-    ui_paused: Signal<bool>,
-    provider: Rc<dyn TimerProvider>,
-    countdown_handle: Rc<RefCell<Option<Box<dyn TimerHandle>>>>,
-    blink_handle: Rc<RefCell<Option<Box<dyn TimerHandle>>>>,
-    remaining_time: Signal<i32>,
-    blinking_signal: Signal<bool>,
-    visible_signal: Signal<bool>,
-    paused_signal: Signal<bool>,
-    reset_time: Signal<Option<i32>>,
+    state: DisplayState,
+    display: ReadSignal<Display>,
+    expiry: ExpiryState,
+    ui_paused: ReadSignal<bool>,
+    warning: ReadSignal<Option<WarningStage>>,
+    theme: ReadSignal<Theme>,
+    reset: ReadSignal<ResetProtection>,
 ) -> View {
+    let allowed = move || reset.with(|r| r.allows(expiry.blinking.get()));
+    let modifier =
+        |ev: &MouseEvent| ev.shift_key() || ev.ctrl_key() || ev.alt_key() || ev.meta_key();
+    let click = move |ev: MouseEvent, double: bool| {
+        if allowed() && reset.with(|r| r.click_resets(double, modifier(&ev))) {
+            timer_client::dispatch(TimerCommand::Reset);
+        }
+    };
+    // The press being held, counted so a release and a new press do not
+    // let the first one's timeout through; zero when none is
+    let holding = create_signal(0u32);
+    let start_hold = move || {
+        if !allowed() || reset.with(|r| r.gesture != ResetGesture::Hold) {
+            return;
+        }
+        let press = holding.get_untracked().wrapping_add(1).max(1);
+        holding.set(press);
+        set_timeout(reset.with_untracked(|r| r.hold_ms) as i32, move || {
+            if holding.get_untracked() == press {
+                holding.set(0);
+                timer_client::dispatch(TimerCommand::Reset);
+            }
+        });
+    };
+    let press = move |ev: MouseEvent| {
+        if ev.button() == 0 {
+            start_hold();
+        }
+    };
+    let release = move |_: MouseEvent| holding.set(0);
+    // `Enter` and `Space` stand in for the mouse button, under the same
+    // gesture: pressed, pressed twice, held, or pressed with a modifier
+    let is_press_key = |ev: &KeyboardEvent| matches!(ev.key().as_str(), "Enter" | " ");
+    let last_key_press = create_signal(f64::NEG_INFINITY);
+    let key_press = move |ev: KeyboardEvent| {
+        if !is_press_key(&ev) {
+            return;
+        }
+        ev.prevent_default();
+        if ev.repeat() {
+            return;
+        }
+        let double = ev.time_stamp() - last_key_press.get_untracked() < DOUBLE_PRESS_MS;
+        last_key_press.set(ev.time_stamp());
+        start_hold();
+        let modifier = ev.shift_key() || ev.ctrl_key() || ev.alt_key() || ev.meta_key();
+        if allowed() && reset.with(|r| r.click_resets(double, modifier)) {
+            timer_client::dispatch(TimerCommand::Reset);
+        }
+    };
+    let key_release = move |ev: KeyboardEvent| {
+        if is_press_key(&ev) {
+            holding.set(0);
+        }
+    };
+
     view! {
         p(
             // Dragging swallows mouseup on macOS, killing the click handler below.
             data-tauri-drag-region="false",
             class="timer-display",
-            style=move || compute_timer_style(
-                ui_blink_active.get(),
-                ui_blink_visible.get(),
-                ui_paused.get(),
-            ),
-            on:click=move |_| {
-                if !paused_signal.get() {
-                    handle_click_and_reset(
-                        provider.clone(),
-                        countdown_handle.clone(),
-                        blink_handle.clone(),
-                        remaining_time.clone(),
-                        blinking_signal.clone(),
-                        visible_signal.clone(),
-                        reset_time.clone(),
-                    );
-                }
-            }
+            // A button to assistive technology, named by the time rather
+            // than whatever the renderer draws
+            role="button",
+            tabindex="0",
+            aria-label=move || {
+                let remaining = state.remaining.get();
+                let time = spoken_time(remaining);
+                let state = if ui_paused.get() { "Paused, " } else { "" };
+                let side = if remaining < 0 { "over" } else { "left" };
+                let action = if allowed() { ". Press to reset" } else { "" };
+                format!("{state}{time} {side}{action}")
+            },
+            style=move || theme.with(|theme| {
+                format!(
+                    "{} {}",
+                    compute_text_style(theme),
+                    compute_timer_style(
+                        expiry.blinking.get(),
+                        expiry.visible.get(),
+                        ui_paused.get(),
+                        warning.get_clone().as_ref(),
+                        &expiry.cues.get_clone(),
+                        &theme.colors,
+                    )
+                )
+            }),
+            // The backend ignores resets while paused
+            on:click=move |ev| click(ev, false),
+            on:dblclick=move |ev| click(ev, true),
+            on:mousedown=press,
+            on:mouseup=release,
+            on:mouseleave=release,
+            on:keydown=key_press,
+            on:keyup=key_release,
         )
         {
-            (formatted_time)
+            // Fills while a hold to reset is under way
+            (if holding.get() != 0 {
+                let duration = format!("animation-duration: {}ms;", reset.with(|r| r.hold_ms));
+                view! { span(class="hold-progress", aria-hidden="true", style=duration) }
+            } else {
+                view! {}
+            })
+            // Marks time up without relying on colour
+            (if expiry.blinking.get() && expiry.cues.with(|cues| cues.prefix) {
+                view! { span(class="expiry-prefix", aria-hidden="true") { "⏰" } }
+            } else {
+                view! {}
+            })
+            (renderer(display.get())(state))
+        }
+    }
+}
+
+/// Polite live region announcing changes of state and milestones, hidden
+/// from sight
+pub fn create_announcement_view(announcement: ReadSignal<String>) -> View {
+    view! {
+        div(class="visually-hidden", role="status", aria-live="polite") {
+            (announcement.get_clone())
         }
     }
 }
+
+/// Brief offer to take back a reset or new duration, which a click on it
+/// does too
+pub fn create_undo_hint_view(shown: ReadSignal<bool>) -> View {
+    view! {
+        (if shown.get() {
+            view! {
+                span(
+                    class="undo-hint",
+                    data-tauri-drag-region="false",
+                    title="Undo: Cmd+Z / Ctrl+Z",
+                    on:click=|_| timer_client::dispatch(TimerCommand::Undo),
+                ) { "↶ Undo" }
+            }
+        } else {
+            view! {}
+        })
+    }
+}
+
+/// Marker while the window lets clicks through, so a faint timer that
+/// cannot be clicked is not mistaken for a stuck one
+pub fn create_click_through_view(enabled: ReadSignal<bool>) -> View {
+    view! {
+        (if enabled.get() {
+            view! {
+                span(class="click-through-marker", title="Click-through: turn off from the tray") { "⌖" }
+            }
+        } else {
+            view! {}
+        })
+    }
+}
+
+/// Lock glyph while the timer ignores the mouse and keys, saying how to
+/// unlock as its tooltip
+pub fn create_lock_view(locked: ReadSignal<bool>, lock: ReadSignal<Lock>) -> View {
+    let title = move || {
+        lock.with(|lock| {
+            if lock.passphrase.is_empty() {
+                format!("Locked: press {} to unlock", lock.chord)
+            } else {
+                "Locked: type the passphrase to unlock".to_string()
+            }
+        })
+    };
+    view! {
+        (if locked.get() {
+            view! {
+                span(class="lock-marker", role="img", aria-label="Locked", title=title) { "🔒" }
+            }
+        } else {
+            view! {}
+        })
+    }
+}
+
+/// Warning badge while the settings file is unusable, with the reason as
+/// its tooltip
+pub fn create_settings_error_view(error: ReadSignal<Option<String>>) -> View {
+    view! {
+        (match error.get_clone() {
+            Some(message) => view! {
+                span(class="settings-error", title=message) { "⚠" }
+            },
+            None => view! {},
+        })
+    }
+}
+
+/// Helper function to create the progress view, drawn under the time and
+/// ignoring the mouse so clicks and drags reach the window
+pub fn create_progress_view(progress: ReadSignal<Progress>, style: ReadSignal<String>) -> View {
+    view! {
+        (match progress.get() {
+            Progress::None => view! {},
+            Progress::Ring => view! {
+                svg(
+                    class="progress-ring",
+                    viewBox="0 0 100 100",
+                    preserveAspectRatio="none",
+                ) {
+                    rect(
+                        x="1",
+                        y="1",
+                        width="98",
+                        height="98",
+                        rx="8",
+                        pathLength="100",
+                        style=style,
+                    )
+                }
+            },
+            Progress::Bar => view! { div(class="progress-bar", style=style) },
+            Progress::Fill => view! { div(class="progress-fill", style=style) },
+        })
+    }
+}
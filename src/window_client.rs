@@ -0,0 +1,13 @@
+//! Sizing the timer window, which the backend does so the new size is
+//! remembered with its position
+
+use crate::ipc;
+
+/// Step the window to its next larger or smaller preset size
+pub fn step_size(larger: bool) {
+    #[derive(serde::Serialize)]
+    struct Args {
+        larger: bool,
+    }
+    ipc::invoke("step_window_size", &Args { larger });
+}